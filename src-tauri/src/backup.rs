@@ -1,31 +1,60 @@
-use crate::config;
-use crate::paths::resolve_template_path;
+use crate::config::{self, GameEntry};
+use crate::manifest::{
+    self, source_entries, AccountSource, ArchiveSource, BackupManifest, HashingReader, ManifestFile,
+    ManifestSummary, MANIFEST_ENTRY,
+};
+use crate::paths::{list_steam_uid, resolve_template_path};
+use crate::retention;
+use crate::settings::Settings;
+use crate::store::{self, BlobStore, SNAPSHOT_EXTENSION};
+use crate::workdir;
 use chrono::{Local, NaiveDateTime, TimeZone};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::fs::{self, File};
-use std::io::{copy, Read, Write};
+use std::io::{copy, BufWriter, Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 use std::time::SystemTime;
 use trash;
 use walkdir::WalkDir;
 use zip::write::FileOptions;
 use zip::{CompressionMethod, ZipWriter};
 use zip::read::ZipArchive;
+use sevenz_rust::{Password, SevenZArchiveEntry, SevenZReader, SevenZWriter};
 
 /// 备份操作返回的结构体
 /// 包含生成的备份文件名与路径、时间戳、备注文件路径（如有）、以及更新后的配置
+/// 存档与最近一次备份完全相同时 `outcome` 为 `unchanged`，此时文件名/路径/时间指向那份已有备份
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BackupResponse {
+    pub outcome: BackupOutcome,
     pub file_name: String,
     pub file_path: String,
     pub timestamp: i64,
     pub remark_path: Option<String>,
+    /// 同名清单副本路径（仅在开启 `manifestSidecar` 时生成）
+    pub manifest_path: Option<String>,
+    /// 按保留策略清理掉的旧备份文件名
+    pub pruned: Vec<String>,
+    /// 清理旧备份时遇到的错误（清理失败不影响本次备份）
+    pub prune_errors: Vec<String>,
     pub config: config::AppConfig,
 }
 
+/// 备份结果
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum BackupOutcome {
+    /// 生成了新的备份文件
+    Created,
+    /// 存档没有变化，未生成新备份，也未更新 lastSave
+    Unchanged,
+}
+
 /// 备份列表中的单项描述
-/// `timestamp` 优先取文件名中的时间；读不到则取文件修改时间；都没有则为 `None`
+/// `timestamp` 优先取文件名中的时间；其次取清单中的创建时间；再读不到则取文件修改时间；都没有则为 `None`
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BackupEntry {
@@ -33,8 +62,42 @@ pub struct BackupEntry {
     pub file_path: String,
     pub timestamp: Option<i64>,
     pub remark: Option<String>,
+    /// 文件大小；去重快照为它引用的不重复内容的总大小（与其他快照共用的内容也计入）
     pub size: u64,
     pub time_source: String,
+    /// 备份清单摘要（旧备份或其他地方下载的备份可能没有）
+    pub manifest: Option<ManifestSummary>,
+    /// 列表只做结构检查（能否读出目录）；完整校验需调用 `verify_backup`
+    pub verify_status: VerifyStatus,
+    /// 已锁定的备份不会被自动清理，删除时需要显式确认
+    pub pinned: bool,
+}
+
+/// 备份文件的校验状态
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum VerifyStatus {
+    /// 目录结构可读，尚未做完整校验
+    Unverified,
+    /// 所有条目 CRC 正确，且与清单一致（如有清单）
+    Verified,
+    /// CRC 错误，或与清单的文件数/大小/哈希不一致
+    Corrupt,
+    /// 无法打开（文件被截断、格式损坏等）
+    Unreadable,
+}
+
+/// 完整校验的结果
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyReport {
+    pub file_name: String,
+    pub status: VerifyStatus,
+    /// 实际读取校验的文件条目数（不含目录与清单）
+    pub checked_entries: usize,
+    pub has_manifest: bool,
+    /// 发现的问题（每项一句说明）
+    pub problems: Vec<String>,
 }
 
 /// 复原操作的返回信息
@@ -42,10 +105,34 @@ pub struct BackupEntry {
 #[serde(rename_all = "camelCase")]
 pub struct RestoreResponse {
     pub config: config::AppConfig,
+    /// 复原到的存档目录，多账号备份时用“；”分隔
     pub restored_path: String,
     pub backup_file: String,
     pub extra_backup_path: Option<String>,
     pub timestamp: i64,
+    /// 解压时因不安全而跳过的条目（符号链接、设备名等）
+    pub rejected_entries: Vec<RejectedEntry>,
+    /// 替换完成但未能移入回收站的旧存档目录（需要用户手动处理）
+    pub stale_save_path: Option<String>,
+    /// 多账号备份复原的 Steam 账号；单账号备份为空
+    pub steam_uids: Vec<String>,
+}
+
+/// 压缩包中被拒绝解压的条目
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RejectedEntry {
+    pub name: String,
+    pub reason: String,
+    /// 是否会写到目标目录之外；存在此类条目时整个备份视为恶意，直接拒绝复原
+    pub escapes: bool,
+}
+
+/// 解压结果：跳过的条目，以及按压缩包记录应当写出的文件数与总字节数（用于校验）
+struct ExtractSummary {
+    rejected: Vec<RejectedEntry>,
+    files: usize,
+    bytes: u64,
 }
 
 /// 复原过程的阶段（用于错误定位）
@@ -53,8 +140,9 @@ pub struct RestoreResponse {
 enum RestoreStage {
     Check,
     ExtraBackup,
-    Delete,
     Extract,
+    Verify,
+    Swap,
     UpdateConfig,
 }
 
@@ -63,8 +151,9 @@ impl RestoreStage {
         match self {
             RestoreStage::Check => "CHECK",
             RestoreStage::ExtraBackup => "EXTRA_BACKUP",
-            RestoreStage::Delete => "DELETE",
             RestoreStage::Extract => "EXTRACT",
+            RestoreStage::Verify => "VERIFY",
+            RestoreStage::Swap => "SWAP",
             RestoreStage::UpdateConfig => "UPDATE_CONFIG",
         }
     }
@@ -74,22 +163,138 @@ fn stage_err(stage: RestoreStage, msg: impl Into<String>) -> String {
     format!("[{}] {}", stage.as_code(), msg.into())
 }
 
-/// 获取/创建备份目录：软件工作目录下的 `backup`
-pub fn backup_dir() -> Result<PathBuf, String> {
-    let workdir = config::software_workdir()?;
+/// 备份文件格式（对应设置项 `backupFormat`："zip" / "7z" / "dedup"）
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum BackupFormat {
+    #[serde(rename = "zip")]
+    Zip,
+    #[serde(rename = "7z")]
+    SevenZ,
+    /// 去重存储：`.snapshot` 文件只保存清单，内容放在备份目录下的 `store`
+    #[serde(rename = "dedup")]
+    Snapshot,
+}
+
+impl BackupFormat {
+    /// 根据文件扩展名识别格式（不区分大小写）
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension().and_then(|s| s.to_str())?;
+        if ext.eq_ignore_ascii_case("zip") {
+            Some(BackupFormat::Zip)
+        } else if ext.eq_ignore_ascii_case("7z") {
+            Some(BackupFormat::SevenZ)
+        } else if ext.eq_ignore_ascii_case(SNAPSHOT_EXTENSION) {
+            Some(BackupFormat::Snapshot)
+        } else {
+            None
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            BackupFormat::Zip => "zip",
+            BackupFormat::SevenZ => "7z",
+            BackupFormat::Snapshot => SNAPSHOT_EXTENSION,
+        }
+    }
+}
+
+/// 单个文件达到此大小时需要写 zip64 扩展头（zip 原生字段只有 32 位）
+const ZIP64_THRESHOLD: u64 = u32::MAX as u64;
+
+/// 按文件大小决定条目选项：达到阈值时开启 zip64
+fn zip_entry_options(options: FileOptions, size: u64) -> FileOptions {
+    options.large_file(size >= ZIP64_THRESHOLD)
+}
+
+/// 默认备份根目录：软件工作目录下的 `backup`
+fn default_backup_root() -> Result<PathBuf, String> {
+    let workdir = workdir::software_workdir()?;
     let dir = workdir.join("backup");
     fs::create_dir_all(&dir).map_err(|e| format!("创建备份目录失败: {e}"))?;
     Ok(dir)
 }
 
+/// 按设置确定备份根目录：设置了 `backupRoot`（例如第二块硬盘或 NAS）时使用该目录，否则为默认目录
+fn backup_root(settings: &Settings) -> Result<PathBuf, String> {
+    match &settings.backup_root {
+        Some(root) => Ok(PathBuf::from(root)),
+        None => default_backup_root(),
+    }
+}
+
+/// 获取/创建备份根目录
+pub fn backup_dir() -> Result<PathBuf, String> {
+    let config = config::read_config()?;
+    let dir = backup_root(&config.settings)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("创建备份目录失败: {e}"))?;
+    Ok(dir)
+}
+
 /// 额外备份目录：软件工作目录下的 `extra-backup`
 fn extra_backup_dir() -> Result<PathBuf, String> {
-    let workdir = config::software_workdir()?;
+    let workdir = workdir::software_workdir()?;
     let dir = workdir.join("extra-backup");
     fs::create_dir_all(&dir).map_err(|e| format!("创建额外备份目录失败: {e}"))?;
     Ok(dir)
 }
 
+/// id 会直接用作目录名，手动改过的配置里可能出现路径分隔符等字符
+pub fn check_game_id(game_id: &str) -> Result<(), String> {
+    if game_id.is_empty() || sanitize_filename(game_id) != game_id {
+        return Err(format!("无效的游戏 id: {game_id}"));
+    }
+    Ok(())
+}
+
+/// 游戏备份目录的位置（不创建）：游戏单独设置了 `backupDir` 时为该目录下的 `<游戏 id>`，否则为备份根目录下的 `<游戏 id>`
+/// 备份只按目录归属，与游戏名无关；多个游戏选了同一个目录也各自独立
+fn locate_game_backup_dir(config: &config::AppConfig, game: &GameEntry) -> Result<PathBuf, String> {
+    check_game_id(&game.id)?;
+    let base = match &game.backup_dir {
+        Some(dir) => PathBuf::from(dir),
+        None => backup_root(&config.settings)?,
+    };
+    Ok(base.join(&game.id))
+}
+
+/// 获取/创建指定游戏的备份目录
+pub fn game_backup_dir(game_id: &str) -> Result<PathBuf, String> {
+    let config = config::read_config()?;
+    let dir = locate_game_backup_dir(&config, config::find_game(&config, game_id)?)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("创建备份目录失败: {e}"))?;
+    Ok(dir)
+}
+
+/// 指定游戏的额外备份目录：`extra-backup/<游戏 id>`
+fn game_extra_backup_dir(game_id: &str) -> Result<PathBuf, String> {
+    check_game_id(game_id)?;
+    let dir = extra_backup_dir()?.join(game_id);
+    fs::create_dir_all(&dir).map_err(|e| format!("创建额外备份目录失败: {e}"))?;
+    Ok(dir)
+}
+
+/// 定位游戏备份目录下的备份文件；文件名只能是单纯的文件名，不能借助路径跳到其他目录
+fn backup_file_path(game_id: &str, file_name: &str) -> Result<PathBuf, String> {
+    if file_name.is_empty() || file_name.contains(['/', '\\']) || file_name == ".." {
+        return Err("无效的备份文件名".to_string());
+    }
+
+    let path = game_backup_dir(game_id)?.join(file_name);
+    if !path.is_file() {
+        return Err("未找到对应的备份文件".to_string());
+    }
+    Ok(path)
+}
+
+/// 去重快照所用的存储：与快照文件位于同一目录（即每个游戏各自一份）
+fn snapshot_store(snapshot_path: &Path) -> Result<BlobStore, String> {
+    let dir = snapshot_path
+        .parent()
+        .ok_or_else(|| "无法获取快照所在目录".to_string())?;
+    BlobStore::open(dir)
+}
+
 /// 过滤文件名中不允许的字符，避免生成非法路径
 fn sanitize_filename(name: &str) -> String {
     let invalid = ["<", ">", ":", "\"", "|", "?", "*", "/", "\\"]; // TODO 我是否应该把加号减号也塞进去？
@@ -109,57 +314,105 @@ fn now_timestamp() -> (String, i64) {
     )
 }
 
-/// 将整个目录压缩为 .zip 文件
-/// 先使用 .zip；直接引入一个 7z 的包感觉有点太重了，，，暂时也不考虑调用外部 7z.exe
-fn zip_directory(src_dir: &Path, dest: &Path) -> Result<(), String> {
-    let file = File::create(dest).map_err(|e| format!("创建备份文件失败: {e}"))?;
-    let mut zip = ZipWriter::new(file);
-    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
-    let mut buffer = Vec::new();
+/// 按指定格式压缩存档目录，并把补全文件明细后的清单写进压缩包
+fn archive_directory(
+    sources: &[ArchiveSource],
+    dest: &Path,
+    format: BackupFormat,
+    manifest: &mut BackupManifest,
+) -> Result<(), String> {
+    match format {
+        BackupFormat::Zip => zip_directory(sources, dest, manifest),
+        BackupFormat::SevenZ => sevenz_directory(sources, dest, manifest),
+        BackupFormat::Snapshot => {
+            let store = snapshot_store(dest)?;
+            store::snapshot_directory(sources, dest, &store, manifest)
+        }
+    }
+}
 
-    for entry in WalkDir::new(src_dir) {
-        let entry = entry.map_err(|e| format!("遍历备份目录失败: {e}"))?;
-        let path = entry.path();
-        let relative = path
-            .strip_prefix(src_dir)
-            .map_err(|e| format!("路径处理失败: {e}"))?;
+/// 按扩展名解压备份文件到目标目录
+fn extract_archive(archive_path: &Path, dest_dir: &Path) -> Result<ExtractSummary, String> {
+    match BackupFormat::from_path(archive_path) {
+        Some(BackupFormat::Zip) => unzip_directory(archive_path, dest_dir),
+        Some(BackupFormat::SevenZ) => un7z_directory(archive_path, dest_dir),
+        Some(BackupFormat::Snapshot) => unsnapshot_directory(archive_path, dest_dir),
+        None => Err("无法识别的备份文件扩展名".to_string()),
+    }
+}
 
-        if relative.as_os_str().is_empty() {
-            continue;
-        }
+/// 将存档目录压缩为 .zip 文件
+/// 文件内容以流的方式写入，内存占用与存档大小无关；超过 4 GiB 的文件使用 zip64
+fn zip_directory(sources: &[ArchiveSource], dest: &Path, manifest: &mut BackupManifest) -> Result<(), String> {
+    let file = File::create(dest).map_err(|e| format!("创建备份文件失败: {e}"))?;
+    let mut zip = ZipWriter::new(BufWriter::new(file));
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
 
-        let name = relative.to_string_lossy().replace('\\', "/");
-        if path.is_file() {
-            // 感谢 AI 不然我真不会写 rust
-            zip.start_file(name, options)
+    for entry in source_entries(sources)? {
+        let path = entry.path.as_path();
+        let name = entry.name;
+        if !entry.is_dir {
+            let f = File::open(path).map_err(|e| format!("读取文件失败: {e}"))?;
+            let size = f
+                .metadata()
+                .map_err(|e| format!("读取文件信息失败: {e}"))?
+                .len();
+            let file_options = zip_entry_options(options, size);
+            zip.start_file(name.as_str(), file_options)
                 .map_err(|e| format!("写入文件到备份包失败: {e}"))?;
-            let mut f = File::open(path).map_err(|e| format!("读取文件失败: {e}"))?;
-            f.read_to_end(&mut buffer)
-                .map_err(|e| format!("读取文件内容失败: {e}"))?;
-            zip.write_all(&buffer)
-                .map_err(|e| format!("写入压缩内容失败: {e}"))?;
-            buffer.clear();
-        } else if path.is_dir() {
+            let mut reader = HashingReader::new(f);
+            let written =
+                copy(&mut reader, &mut zip).map_err(|e| format!("写入压缩内容失败: {e}"))?;
+            manifest.files.push(ManifestFile {
+                path: name,
+                size: written,
+                modified: file_modified_millis(path),
+                sha256: reader.finish(),
+            });
+        } else {
             let dir_name = format!("{}/", name.trim_end_matches('/'));
             zip.add_directory(dir_name, options)
                 .map_err(|e| format!("写入目录到备份包失败: {e}"))?;
         }
     }
 
-    zip.finish().map_err(|e| format!("完成压缩失败: {e}"))?;
+    zip.start_file(MANIFEST_ENTRY, options)
+        .map_err(|e| format!("写入备份清单失败: {e}"))?;
+    zip.write_all(manifest.to_json()?.as_bytes())
+        .map_err(|e| format!("写入备份清单失败: {e}"))?;
+
+    // BufWriter 析构时的写入错误会被吞掉，这里显式 flush
+    let mut writer = zip.finish().map_err(|e| format!("完成压缩失败: {e}"))?;
+    writer.flush().map_err(|e| format!("完成压缩失败: {e}"))?;
     Ok(())
 }
 
 /// 将 zip 文件解压到目标目录（会按需创建子目录）
-fn unzip_directory(zip_path: &Path, dest_dir: &Path) -> Result<(), String> {
+/// 条目名称逐一校验，不安全的条目跳过并返回给调用方
+fn unzip_directory(zip_path: &Path, dest_dir: &Path) -> Result<ExtractSummary, String> {
     let file = File::open(zip_path).map_err(|e| format!("读取备份文件失败: {e}"))?;
     let mut archive = ZipArchive::new(file).map_err(|e| format!("解析 Zip 失败: {e}"))?;
+    let mut summary = ExtractSummary {
+        rejected: Vec::new(),
+        files: 0,
+        bytes: 0,
+    };
 
     for i in 0..archive.len() {
         let mut entry = archive
             .by_index(i)
             .map_err(|e| format!("读取压缩条目失败: {e}"))?;
-        let out_path = dest_dir.join(entry.mangled_name());
+        if entry.name() == MANIFEST_ENTRY {
+            continue;
+        }
+        let relative = match check_zip_entry(&entry) {
+            Ok(p) => p,
+            Err(r) => {
+                summary.rejected.push(r);
+                continue;
+            }
+        };
+        let out_path = dest_dir.join(relative);
 
         if entry.is_dir() {
             fs::create_dir_all(&out_path)
@@ -176,11 +429,546 @@ fn unzip_directory(zip_path: &Path, dest_dir: &Path) -> Result<(), String> {
             .map_err(|e| format!("写出文件失败: {e}"))?;
         copy(&mut entry, &mut outfile)
             .map_err(|e| format!("解压写入失败: {e}"))?;
+        summary.files += 1;
+        summary.bytes += entry.size();
+    }
+
+    Ok(summary)
+}
+
+/// 将存档目录压缩为 .7z 文件（纯 Rust 实现，LZMA2，每个文件单独成块）
+fn sevenz_directory(sources: &[ArchiveSource], dest: &Path, manifest: &mut BackupManifest) -> Result<(), String> {
+    let mut sz = SevenZWriter::create(dest).map_err(|e| format!("创建备份文件失败: {e}"))?;
+
+    for entry in source_entries(sources)? {
+        let path = entry.path.as_path();
+        let name = entry.name;
+        if !entry.is_dir {
+            let f = File::open(path).map_err(|e| format!("读取文件失败: {e}"))?;
+            let mut reader = HashingReader::new(f);
+            let size = sz
+                .push_archive_entry(
+                    SevenZArchiveEntry::from_path(path, name.clone()),
+                    Some(&mut reader),
+                )
+                .map_err(|e| format!("写入文件到备份包失败: {e}"))?
+                .size;
+            manifest.files.push(ManifestFile {
+                path: name,
+                size,
+                modified: file_modified_millis(path),
+                sha256: reader.finish(),
+            });
+        } else {
+            sz.push_archive_entry::<File>(SevenZArchiveEntry::from_path(path, name), None)
+                .map_err(|e| format!("写入目录到备份包失败: {e}"))?;
+        }
+    }
+
+    let mut manifest_entry = SevenZArchiveEntry::new();
+    manifest_entry.name = MANIFEST_ENTRY.to_string();
+    let content = manifest.to_json()?;
+    sz.push_archive_entry(manifest_entry, Some(content.as_bytes()))
+        .map_err(|e| format!("写入备份清单失败: {e}"))?;
+
+    sz.finish().map_err(|e| format!("完成压缩失败: {e}"))?;
+    Ok(())
+}
+
+/// 将 7z 文件解压到目标目录
+/// 不使用库自带的落盘逻辑（它直接拼接条目名），改为逐条校验后自行写出
+fn un7z_directory(archive_path: &Path, dest_dir: &Path) -> Result<ExtractSummary, String> {
+    let mut summary = ExtractSummary {
+        rejected: Vec::new(),
+        files: 0,
+        bytes: 0,
+    };
+
+    sevenz_rust::decompress_file_with_extract_fn(archive_path, dest_dir, |entry, reader, _| {
+        if entry.name() == MANIFEST_ENTRY {
+            copy(reader, &mut std::io::sink()).map_err(sevenz_rust::Error::io)?;
+            return Ok(true);
+        }
+        let relative = match check_7z_entry(entry) {
+            Ok(p) => p,
+            Err(r) => {
+                summary.rejected.push(r);
+                // 固实压缩时后续条目依赖前面的数据，跳过的条目也要读完
+                copy(reader, &mut std::io::sink()).map_err(sevenz_rust::Error::io)?;
+                return Ok(true);
+            }
+        };
+        let out_path = dest_dir.join(relative);
+
+        if entry.is_directory() {
+            fs::create_dir_all(&out_path).map_err(sevenz_rust::Error::io)?;
+            return Ok(true);
+        }
+
+        if let Some(parent) = out_path.parent() {
+            fs::create_dir_all(parent).map_err(sevenz_rust::Error::io)?;
+        }
+
+        let mut outfile = File::create(&out_path).map_err(sevenz_rust::Error::io)?;
+        copy(reader, &mut outfile).map_err(sevenz_rust::Error::io)?;
+        summary.files += 1;
+        summary.bytes += entry.size();
+        Ok(true)
+    })
+    .map_err(|e| format!("解压 7z 失败: {e}"))?;
+
+    Ok(summary)
+}
+
+/// 将去重快照还原到目标目录：先重建记录的目录，再按清单从存储中取出内容，边复制边核对哈希
+fn unsnapshot_directory(snapshot_path: &Path, dest_dir: &Path) -> Result<ExtractSummary, String> {
+    let snapshot = store::read_snapshot(snapshot_path)?;
+    let store = snapshot_store(snapshot_path)?;
+    let mut summary = ExtractSummary {
+        rejected: Vec::new(),
+        files: 0,
+        bytes: 0,
+    };
+
+    for dir in &snapshot.dirs {
+        match sanitize_entry_name(dir) {
+            Ok(relative) => fs::create_dir_all(dest_dir.join(relative))
+                .map_err(|e| format!("创建目录失败: {e}"))?,
+            Err(r) => summary.rejected.push(r),
+        }
+    }
+
+    for file in &snapshot.files {
+        let relative = match sanitize_entry_name(&file.path) {
+            Ok(p) => p,
+            Err(r) => {
+                summary.rejected.push(r);
+                continue;
+            }
+        };
+        let out_path = dest_dir.join(relative);
+        if let Some(parent) = out_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("创建父目录失败: {e}"))?;
+        }
+
+        let mut reader = HashingReader::new(store.open_blob(&file.sha256)?);
+        let mut outfile = File::create(&out_path)
+            .map_err(|e| format!("写出文件失败: {e}"))?;
+        copy(&mut reader, &mut outfile)
+            .map_err(|e| format!("解压写入失败: {e}"))?;
+        if reader.finish() != file.sha256 {
+            return Err(format!("去重存储中的内容已损坏: {}", file.path));
+        }
+        summary.files += 1;
+        summary.bytes += file.size;
+    }
+
+    Ok(summary)
+}
+
+/// Windows 保留的设备名：即使带扩展名（如 `NUL.txt`）也会被系统当作设备打开
+const WINDOWS_DEVICE_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+fn is_windows_device_name(part: &str) -> bool {
+    let stem = part.split('.').next().unwrap_or("").trim_end();
+    WINDOWS_DEVICE_NAMES
+        .iter()
+        .any(|d| d.eq_ignore_ascii_case(stem))
+}
+
+/// 校验压缩条目名称，返回可以安全拼接到目标目录下的相对路径
+/// 拒绝绝对路径、盘符/UNC 前缀、`..` 越界以及 Windows 设备名；其中会写出目标目录的条目标记为 `escapes`
+fn sanitize_entry_name(name: &str) -> Result<PathBuf, RejectedEntry> {
+    let reject = |reason: &str, escapes: bool| RejectedEntry {
+        name: name.to_string(),
+        reason: reason.to_string(),
+        escapes,
+    };
+
+    let normalized = name.replace('\\', "/");
+    if normalized.starts_with('/') {
+        return Err(reject("绝对路径", true));
+    }
+
+    let mut out = PathBuf::new();
+    for part in normalized.split('/') {
+        if part.is_empty() || part == "." {
+            continue;
+        }
+        // Windows 会忽略结尾的点和空格，".. " 之类的写法同样会越界
+        if part.trim_end_matches(['.', ' ']).is_empty() {
+            return Err(reject("路径越界（..）", true));
+        }
+        if part.contains(':') {
+            return Err(reject("包含盘符或数据流（:）", true));
+        }
+        if part.chars().any(|c| c.is_control()) {
+            return Err(reject("包含控制字符", false));
+        }
+        if is_windows_device_name(part) {
+            return Err(reject("Windows 设备名", false));
+        }
+        out.push(part);
+    }
+
+    if out.as_os_str().is_empty() {
+        return Err(reject("空路径", false));
+    }
+    Ok(out)
+}
+
+/// unix 权限位中的文件类型掩码与符号链接类型
+const S_IFMT: u32 = 0o170000;
+const S_IFLNK: u32 = 0o120000;
+
+fn check_zip_entry(entry: &zip::read::ZipFile) -> Result<PathBuf, RejectedEntry> {
+    let name = entry.name().to_string();
+    if entry.unix_mode().is_some_and(|m| m & S_IFMT == S_IFLNK) {
+        return Err(RejectedEntry {
+            name,
+            reason: "符号链接".to_string(),
+            escapes: false,
+        });
+    }
+    sanitize_entry_name(&name)
+}
+
+fn check_7z_entry(entry: &SevenZArchiveEntry) -> Result<PathBuf, RejectedEntry> {
+    // 0x400: FILE_ATTRIBUTE_REPARSE_POINT；0x8000: 高 16 位存放 unix 权限（p7zip 的扩展）
+    let attrs = entry.windows_attributes();
+    let is_link = entry.has_windows_attributes
+        && (attrs & 0x400 != 0 || (attrs & 0x8000 != 0 && (attrs >> 16) & S_IFMT == S_IFLNK));
+    if is_link {
+        return Err(RejectedEntry {
+            name: entry.name().to_string(),
+            reason: "符号链接".to_string(),
+            escapes: false,
+        });
+    }
+    sanitize_entry_name(entry.name())
+}
+
+/// 读取压缩包内嵌的清单；没有清单（旧备份/外部备份）或读取失败时返回 `None`
+fn read_embedded_manifest(archive_path: &Path) -> Option<BackupManifest> {
+    let mut content = String::new();
+    match BackupFormat::from_path(archive_path)? {
+        BackupFormat::Zip => {
+            let file = File::open(archive_path).ok()?;
+            let mut archive = ZipArchive::new(file).ok()?;
+            let mut entry = archive.by_name(MANIFEST_ENTRY).ok()?;
+            entry.read_to_string(&mut content).ok()?;
+        }
+        BackupFormat::SevenZ => {
+            let mut found = false;
+            let mut reader = SevenZReader::open(archive_path, Password::empty()).ok()?;
+            reader
+                .for_each_entries(|entry, r| {
+                    if entry.name() != MANIFEST_ENTRY {
+                        // 清单写在最后；本程序生成的 7z 非固实，不读取内容即可直接跳过
+                        return Ok(true);
+                    }
+                    r.read_to_string(&mut content)
+                        .map_err(sevenz_rust::Error::io)?;
+                    found = true;
+                    Ok(false)
+                })
+                .ok()?;
+            if !found {
+                return None;
+            }
+        }
+        BackupFormat::Snapshot => return store::read_snapshot(archive_path).ok(),
+    }
+    BackupManifest::from_json(&content).ok()
+}
+
+/// 读取备份清单：优先读同名副本，没有再读压缩包内嵌的清单
+fn read_backup_manifest(archive_path: &Path) -> Option<BackupManifest> {
+    manifest::read_sidecar(archive_path).or_else(|| read_embedded_manifest(archive_path))
+}
+
+/// 结构检查：只解析压缩包目录，不解压内容；能发现截断等问题
+fn quick_check(archive_path: &Path) -> VerifyStatus {
+    let readable = match BackupFormat::from_path(archive_path) {
+        Some(BackupFormat::Zip) => File::open(archive_path)
+            .ok()
+            .and_then(|f| ZipArchive::new(f).ok())
+            .is_some(),
+        Some(BackupFormat::SevenZ) => sevenz_rust::Archive::open(archive_path).is_ok(),
+        Some(BackupFormat::Snapshot) => {
+            // 快照可读且引用的内容都还在
+            let store = snapshot_store(archive_path);
+            match (store::read_snapshot(archive_path), store) {
+                (Ok(snapshot), Ok(store)) => {
+                    snapshot.files.iter().all(|f| store.contains(&f.sha256))
+                }
+                _ => false,
+            }
+        }
+        None => false,
+    };
+    if readable {
+        VerifyStatus::Unverified
+    } else {
+        VerifyStatus::Unreadable
+    }
+}
+
+/// 压缩包中单个文件条目的实际内容摘要
+struct EntryDigest {
+    name: String,
+    size: u64,
+    sha256: String,
+}
+
+/// 完整读取压缩包中的每个文件条目：读到结尾时由解压库校验 CRC，同时计算 SHA-256
+/// 返回各条目摘要与内嵌清单（如有）；任意条目 CRC 错误或读取失败都会返回 `Err`
+fn digest_archive(archive_path: &Path) -> Result<(Vec<EntryDigest>, Option<BackupManifest>), String> {
+    let mut digests = Vec::new();
+    let mut manifest_content: Option<String> = None;
+
+    match BackupFormat::from_path(archive_path) {
+        Some(BackupFormat::Zip) => {
+            let file = File::open(archive_path).map_err(|e| format!("读取备份文件失败: {e}"))?;
+            let mut archive =
+                ZipArchive::new(file).map_err(|e| format!("解析 Zip 失败: {e}"))?;
+            for i in 0..archive.len() {
+                let mut entry = archive
+                    .by_index(i)
+                    .map_err(|e| format!("读取压缩条目失败: {e}"))?;
+                if entry.is_dir() {
+                    continue;
+                }
+                let name = entry.name().to_string();
+                if name == MANIFEST_ENTRY {
+                    let mut content = String::new();
+                    entry
+                        .read_to_string(&mut content)
+                        .map_err(|e| format!("读取备份清单失败: {e}"))?;
+                    manifest_content = Some(content);
+                    continue;
+                }
+                let mut reader = HashingReader::new(&mut entry);
+                let size = copy(&mut reader, &mut std::io::sink())
+                    .map_err(|e| format!("条目 {name} 校验失败: {e}"))?;
+                digests.push(EntryDigest {
+                    name,
+                    size,
+                    sha256: reader.finish(),
+                });
+            }
+        }
+        Some(BackupFormat::SevenZ) => {
+            let mut reader = SevenZReader::open(archive_path, Password::empty())
+                .map_err(|e| format!("解析 7z 失败: {e}"))?;
+            reader
+                .for_each_entries(|entry, r| {
+                    if entry.is_directory() {
+                        return Ok(true);
+                    }
+                    if entry.name() == MANIFEST_ENTRY {
+                        let mut content = String::new();
+                        r.read_to_string(&mut content)
+                            .map_err(sevenz_rust::Error::io)?;
+                        manifest_content = Some(content);
+                        return Ok(true);
+                    }
+                    let mut hashing = HashingReader::new(r);
+                    let size = copy(&mut hashing, &mut std::io::sink()).map_err(|e| {
+                        sevenz_rust::Error::io_msg(e, format!("条目 {} 校验失败", entry.name()))
+                    })?;
+                    digests.push(EntryDigest {
+                        name: entry.name().to_string(),
+                        size,
+                        sha256: hashing.finish(),
+                    });
+                    Ok(true)
+                })
+                .map_err(|e| format!("7z 校验失败: {e}"))?;
+        }
+        Some(BackupFormat::Snapshot) => {
+            let snapshot = store::read_snapshot(archive_path)?;
+            let store = snapshot_store(archive_path)?;
+            for file in &snapshot.files {
+                let mut reader = HashingReader::new(store.open_blob(&file.sha256)?);
+                let size = copy(&mut reader, &mut std::io::sink())
+                    .map_err(|e| format!("条目 {} 校验失败: {e}", file.path))?;
+                digests.push(EntryDigest {
+                    name: file.path.clone(),
+                    size,
+                    sha256: reader.finish(),
+                });
+            }
+            return Ok((digests, Some(snapshot)));
+        }
+        None => return Err("无法识别的备份文件扩展名".to_string()),
+    }
+
+    let manifest = match manifest_content {
+        Some(content) => Some(BackupManifest::from_json(&content)?),
+        None => None,
+    };
+    Ok((digests, manifest))
+}
+
+/// 将实际内容与清单逐项比对，返回发现的问题
+fn compare_with_manifest(digests: &[EntryDigest], manifest: &BackupManifest) -> Vec<String> {
+    use std::collections::HashMap;
+
+    let mut problems = Vec::new();
+    let mut actual: HashMap<&str, &EntryDigest> =
+        digests.iter().map(|d| (d.name.as_str(), d)).collect();
+
+    if digests.len() != manifest.files.len() {
+        problems.push(format!(
+            "文件数不一致：清单记录 {} 个，压缩包中有 {} 个",
+            manifest.files.len(),
+            digests.len()
+        ));
+    }
+
+    for file in &manifest.files {
+        let Some(d) = actual.remove(file.path.as_str()) else {
+            problems.push(format!("缺少文件: {}", file.path));
+            continue;
+        };
+        if d.size != file.size {
+            problems.push(format!(
+                "大小不一致: {}（清单 {} 字节，实际 {} 字节）",
+                file.path, file.size, d.size
+            ));
+        } else if !d.sha256.eq_ignore_ascii_case(&file.sha256) {
+            problems.push(format!("哈希不一致: {}", file.path));
+        }
+    }
+
+    let mut extra: Vec<&str> = actual.into_keys().collect();
+    extra.sort();
+    for name in extra {
+        problems.push(format!("清单中没有记录的文件: {name}"));
+    }
+    problems
+}
+
+/// 预检备份文件中的全部条目（只读目录，不解压），返回会被拒绝的条目
+fn inspect_archive(archive_path: &Path) -> Result<Vec<RejectedEntry>, String> {
+    match BackupFormat::from_path(archive_path) {
+        Some(BackupFormat::Zip) => {
+            let file = File::open(archive_path).map_err(|e| format!("读取备份文件失败: {e}"))?;
+            let mut archive =
+                ZipArchive::new(file).map_err(|e| format!("解析 Zip 失败: {e}"))?;
+            let mut rejected = Vec::new();
+            for i in 0..archive.len() {
+                let entry = archive
+                    .by_index_raw(i)
+                    .map_err(|e| format!("读取压缩条目失败: {e}"))?;
+                if let Err(r) = check_zip_entry(&entry) {
+                    rejected.push(r);
+                }
+            }
+            Ok(rejected)
+        }
+        Some(BackupFormat::SevenZ) => {
+            let archive = sevenz_rust::Archive::open(archive_path)
+                .map_err(|e| format!("解析 7z 失败: {e}"))?;
+            Ok(archive
+                .files
+                .iter()
+                .filter_map(|f| check_7z_entry(f).err())
+                .collect())
+        }
+        Some(BackupFormat::Snapshot) => {
+            let snapshot = store::read_snapshot(archive_path)?;
+            Ok(snapshot
+                .dirs
+                .iter()
+                .chain(snapshot.files.iter().map(|f| &f.path))
+                .filter_map(|name| sanitize_entry_name(name).err())
+                .collect())
+        }
+        None => Err("无法识别的备份文件扩展名".to_string()),
+    }
+}
+
+/// 复原用的同级目录（临时解压 / 暂存旧存档）：与存档目录同一父目录，保证 rename 不跨盘
+fn restore_sibling(target: &Path, kind: &str, ts_tag: &str) -> Result<PathBuf, String> {
+    let name = target
+        .file_name()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| "无法识别存档目录名称".to_string())?;
+    Ok(target.with_file_name(format!(".{name}.game-sl-{kind}-{ts_tag}")))
+}
+
+/// 处理上一次被中断的复原：删除残留的临时目录；存档目录缺失时把暂存的旧存档改名回来
+fn recover_interrupted_restore(target: &Path) -> Result<(), String> {
+    let (Some(parent), Some(name)) = (target.parent(), target.file_name().and_then(|s| s.to_str()))
+    else {
+        return Ok(());
+    };
+    let Ok(entries) = fs::read_dir(parent) else {
+        return Ok(());
+    };
+
+    let staging_prefix = format!(".{name}.game-sl-staging-");
+    let previous_prefix = format!(".{name}.game-sl-previous-");
+    let mut previous = Vec::new();
+    for entry in entries.flatten() {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if file_name.starts_with(&staging_prefix) {
+            let _ = fs::remove_dir_all(entry.path());
+        } else if file_name.starts_with(&previous_prefix) {
+            previous.push(entry.path());
+        }
+    }
+
+    // 时间标签按字典序即可比较先后，取最新的一份
+    previous.sort();
+    if !target.exists() {
+        if let Some(latest) = previous.pop() {
+            fs::rename(&latest, target).map_err(|e| format!("恢复被中断复原的原存档失败: {e}"))?;
+        }
+    }
+
+    // 剩下的说明替换已完成，只是没来得及移入回收站
+    for p in previous {
+        let _ = trash::delete(&p);
+    }
+    Ok(())
+}
+
+/// 校验临时目录中的解压结果与压缩包记录是否一致
+fn verify_extracted(dir: &Path, summary: &ExtractSummary) -> Result<(), String> {
+    let mut files = 0usize;
+    let mut bytes = 0u64;
+    for entry in WalkDir::new(dir) {
+        let entry = entry.map_err(|e| format!("遍历解压目录失败: {e}"))?;
+        if entry.file_type().is_file() {
+            files += 1;
+            bytes += entry
+                .metadata()
+                .map_err(|e| format!("读取文件信息失败: {e}"))?
+                .len();
+        }
     }
 
+    if files != summary.files || bytes != summary.bytes {
+        return Err(format!(
+            "解压结果与备份不一致：应有 {} 个文件共 {} 字节，实际 {} 个文件共 {} 字节",
+            summary.files, summary.bytes, files, bytes
+        ));
+    }
     Ok(())
 }
 
+/// 锁定标记文件：与备注一样放在压缩包旁边，`xxx.zip` -> `xxx.pinned`
+fn pin_path(archive_path: &Path) -> PathBuf {
+    archive_path.with_extension("pinned")
+}
+
 /// 从备份文件名中解析时间戳：{游戏名}-Backup-YYYYMMDD-HHMMSS.ext
 fn parse_timestamp_from_name(name: &str) -> Option<i64> {
     // {Game}-Backup-YYYYMMDD-HHMMSS.ext
@@ -199,25 +987,147 @@ fn file_modified_millis(path: &Path) -> Option<i64> {
     Some(duration.as_millis() as i64)
 }
 
-/// 备份：解析模板路径、压缩存档目录、写备注文件（可选）、更新配置中的最后备份时间 lastSave
+/// 找到与当前存档内容完全相同的最近一次备份；最近的备份没有清单时视为有变化
+fn find_unchanged_backup(
+    game_id: &str,
+    sources: &[ArchiveSource],
+) -> Result<Option<BackupEntry>, String> {
+    let Some(latest) = list_backups(game_id.to_string())?.into_iter().next() else {
+        return Ok(None);
+    };
+    let Some(manifest) = read_backup_manifest(Path::new(&latest.file_path)) else {
+        return Ok(None);
+    };
+
+    if manifest::matches_sources(&manifest.files, sources)? {
+        Ok(Some(latest))
+    } else {
+        Ok(None)
+    }
+}
+
+/// 多账号备份：本机每个 Steam 账号解析出的存档路径（不检查是否存在）
+fn account_save_paths(
+    path_template: &str,
+    app_id: Option<u32>,
+) -> Result<Vec<(String, PathBuf)>, String> {
+    if !path_template.contains("{SteamUID}") {
+        return Err("存档路径中没有 {SteamUID}，无法按账号备份".to_string());
+    }
+    list_steam_uid()
+        .into_iter()
+        .map(|uid| {
+            let path = resolve_template_path(path_template.to_string(), Some(uid.clone()), app_id)?;
+            Ok((uid, path))
+        })
+        .collect()
+}
+
+/// 多账号备份：列出本机所有 Steam 账号中存档路径存在的账号及其存档目录（只读，不处理中断的复原）
+pub fn list_account_saves(
+    path_template: &str,
+    app_id: Option<u32>,
+) -> Result<Vec<(String, PathBuf)>, String> {
+    Ok(account_save_paths(path_template, app_id)?
+        .into_iter()
+        .filter(|(_, path)| path.exists())
+        .collect())
+}
+
+/// 备份：解析模板路径、压缩存档目录、写备注文件（可选）、更新配置中的最后备份时间 lastSave，最后按保留策略清理旧备份
+/// 存档没有变化时直接返回 `Unchanged`，`force` 为 true 时无论如何都生成新备份
+/// `all_steam_uids` 为 true 时忽略 `steam_uid`，把所有存在存档的 Steam 账号放进同一个备份，
+/// 每个账号的存档位于压缩包中以账号 id 命名的目录下
 pub fn perform_backup(
-    game_name: String,
+    game_id: String,
     path_template: String,
     steam_uid: Option<String>,
     remark: Option<String>,
+    force: bool,
+    all_steam_uids: bool,
 ) -> Result<BackupResponse, String> {
-    let source_path = resolve_template_path(path_template, steam_uid)?;
-    if !source_path.exists() {
-        return Err("存档路径不存在，无法备份".to_string());
+    let config_snapshot = config::read_config()?;
+    let game = config::find_game(&config_snapshot, &game_id)?;
+    let game_name = game.name.clone();
+
+    let (source_path, steam_uid, account_saves) = if all_steam_uids {
+        let mut saves = Vec::new();
+        for (uid, path) in account_save_paths(&path_template, game.steam_app_id)? {
+            recover_interrupted_restore(&path)?;
+            if path.exists() {
+                saves.push((uid, path));
+            }
+        }
+        if saves.is_empty() {
+            return Err("没有找到任何 Steam 账号的存档，无法备份".to_string());
+        }
+        (PathBuf::from(&path_template), None, saves)
+    } else {
+        let source_path =
+            resolve_template_path(path_template, steam_uid.clone(), game.steam_app_id)?;
+        recover_interrupted_restore(&source_path)?;
+        if !source_path.exists() {
+            return Err("存档路径不存在，无法备份".to_string());
+        }
+        (source_path, steam_uid, Vec::new())
+    };
+    let sources: Vec<ArchiveSource> = if account_saves.is_empty() {
+        vec![ArchiveSource::root(&source_path)]
+    } else {
+        account_saves
+            .iter()
+            .map(|(uid, path)| ArchiveSource::account(uid, path))
+            .collect()
+    };
+
+    // 存档与最近一次备份完全相同时不再重复备份（除非强制）
+    if !force {
+        if let Some(latest) = find_unchanged_backup(&game_id, &sources)? {
+            return Ok(BackupResponse {
+                outcome: BackupOutcome::Unchanged,
+                file_name: latest.file_name,
+                file_path: latest.file_path,
+                timestamp: latest.timestamp.unwrap_or_default(),
+                remark_path: None,
+                manifest_path: None,
+                pruned: Vec::new(),
+                prune_errors: Vec::new(),
+                config: config_snapshot,
+            });
+        }
     }
 
-    let target_dir = backup_dir()?;
+    let format = config_snapshot.settings.backup_format;
+    let write_sidecar = config_snapshot.settings.manifest_sidecar;
+    let target_dir = game_backup_dir(&game_id)?;
     let safe_name = sanitize_filename(&game_name);
     let (ts_tag, ts_millis) = now_timestamp();
     let file_stem = format!("{safe_name}-Backup-{ts_tag}");
-    let archive_path = target_dir.join(format!("{file_stem}.zip"));
-
-    zip_directory(&source_path, &archive_path)?;
+    let archive_name = format!("{file_stem}.{}", format.extension());
+    let archive_path = target_dir.join(&archive_name);
+
+    let mut manifest = BackupManifest::new(
+        &game_name,
+        &source_path,
+        steam_uid,
+        config_snapshot.version,
+        ts_millis,
+    );
+    manifest.accounts = account_saves
+        .iter()
+        .map(|(uid, path)| AccountSource {
+            steam_uid: uid.clone(),
+            source_path: path.to_string_lossy().to_string(),
+        })
+        .collect();
+    archive_directory(&sources, &archive_path, format, &mut manifest)?;
+
+    let manifest_path = if write_sidecar {
+        let path = manifest::write_sidecar(&archive_path, &manifest)?;
+        Some(path.to_string_lossy().to_string())
+    } else {
+        None
+    };
 
     let mut remark_path: Option<String> = None;
     if let Some(text) = remark {
@@ -226,21 +1136,27 @@ pub fn perform_backup(
         remark_path = Some(note_path.to_string_lossy().to_string());
     }
 
-    let config = config::update_last_save(&game_name, ts_millis)?;
+    let config = config::update_last_save(&game_id, ts_millis)?;
+
+    // 新备份已经落盘，再按保留策略清理旧备份；清理失败（例如网络目录上送回收站失败）只在结果中提示
+    let (pruned, prune_errors) = retention::prune_after_backup(&game_id);
 
     Ok(BackupResponse {
-        file_name: format!("{file_stem}.zip"),
+        outcome: BackupOutcome::Created,
+        file_name: archive_name,
         file_path: archive_path.to_string_lossy().to_string(),
         timestamp: ts_millis,
         remark_path,
+        manifest_path,
+        pruned,
+        prune_errors,
         config,
     })
 }
 
-/// 列出指定游戏的备份文件（目前考虑 .zip/.7z），并尝试读取备注与时间信息
-pub fn list_backups(game_name: String) -> Result<Vec<BackupEntry>, String> {
-    let dir = backup_dir()?;
-    let filename_prefix = format!("{}-Backup", sanitize_filename(&game_name));
+/// 列出指定游戏的备份文件（.zip/.7z/.snapshot），并尝试读取备注与时间信息
+pub fn list_backups(game_id: String) -> Result<Vec<BackupEntry>, String> {
+    let dir = game_backup_dir(&game_id)?;
     let entries = match fs::read_dir(&dir) {
         Ok(e) => e,
         Err(_) => return Ok(Vec::new()),
@@ -260,14 +1176,11 @@ pub fn list_backups(game_name: String) -> Result<Vec<BackupEntry>, String> {
             continue;
         };
 
-        if !(file_name.ends_with(".zip") || file_name.ends_with(".7z")) {
-            continue;
-        }
-
-        if !file_name.starts_with(&filename_prefix) {
+        if BackupFormat::from_path(&path).is_none() {
             continue;
         }
 
+        let manifest = read_backup_manifest(&path);
         let timestamp = parse_timestamp_from_name(&file_name);
         let remark_path = path.with_extension("txt");
         let remark = if remark_path.exists() {
@@ -275,17 +1188,33 @@ pub fn list_backups(game_name: String) -> Result<Vec<BackupEntry>, String> {
         } else {
             None
         };
-        let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        // 快照文件本身只是清单，展示其引用的内容在存储中的大小（相同内容只算一次）
+        let size = match (BackupFormat::from_path(&path), &manifest) {
+            (Some(BackupFormat::Snapshot), Some(m)) => {
+                let mut seen = HashSet::new();
+                m.files
+                    .iter()
+                    .filter(|f| seen.insert(f.sha256.as_str()))
+                    .map(|f| f.size)
+                    .sum()
+            }
+            _ => fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+        };
 
+        let manifest_ts = manifest.as_ref().map(|m| m.created_at);
         let time_source = if timestamp.is_some() {
             "file-name".to_string()
+        } else if manifest_ts.is_some() {
+            "manifest".to_string()
         } else if file_modified_millis(&path).is_some() {
             "modified-time".to_string()
         } else {
             "unknown".to_string()
         };
 
-        let fallback_ts = timestamp.or_else(|| file_modified_millis(&path));
+        let fallback_ts = timestamp
+            .or(manifest_ts)
+            .or_else(|| file_modified_millis(&path));
 
         backups.push(BackupEntry {
             file_name: file_name.clone(),
@@ -294,6 +1223,9 @@ pub fn list_backups(game_name: String) -> Result<Vec<BackupEntry>, String> {
             remark,
             size,
             time_source,
+            manifest: manifest.map(|m| m.summary()),
+            verify_status: quick_check(&path),
+            pinned: pin_path(&path).exists(),
         });
     }
 
@@ -301,23 +1233,59 @@ pub fn list_backups(game_name: String) -> Result<Vec<BackupEntry>, String> {
     Ok(backups)
 }
 
+/// 完整校验指定备份：检查每个条目的 CRC；有清单时再比对文件数、大小与 SHA-256
+pub fn verify_backup(game_id: String, file_name: String) -> Result<VerifyReport, String> {
+    let archive_path = backup_file_path(&game_id, &file_name)?;
+
+    if quick_check(&archive_path) == VerifyStatus::Unreadable {
+        return Ok(VerifyReport {
+            file_name,
+            status: VerifyStatus::Unreadable,
+            checked_entries: 0,
+            has_manifest: false,
+            problems: vec!["无法读取压缩包目录，文件可能已被截断或损坏".to_string()],
+        });
+    }
+
+    let (digests, manifest) = match digest_archive(&archive_path) {
+        Ok(r) => r,
+        Err(e) => {
+            return Ok(VerifyReport {
+                file_name,
+                status: VerifyStatus::Corrupt,
+                checked_entries: 0,
+                has_manifest: false,
+                problems: vec![e],
+            })
+        }
+    };
+
+    let problems = manifest
+        .as_ref()
+        .map(|m| compare_with_manifest(&digests, m))
+        .unwrap_or_default();
+    let status = if problems.is_empty() {
+        VerifyStatus::Verified
+    } else {
+        VerifyStatus::Corrupt
+    };
+
+    Ok(VerifyReport {
+        file_name,
+        status,
+        checked_entries: digests.len(),
+        has_manifest: manifest.is_some(),
+        problems,
+    })
+}
+
 /// 更新（或删除）指定备份文件的备注：备注存储在同名 .txt 文件
 pub fn update_backup_remark(
-    game_name: String,
+    game_id: String,
     file_name: String,
     remark: String,
 ) -> Result<(), String> {
-    let dir = backup_dir()?;
-    let filename_prefix = format!("{}-Backup", sanitize_filename(&game_name));
-
-    if !file_name.starts_with(&filename_prefix) {
-        return Err("文件名与游戏不匹配".to_string());
-    }
-
-    let archive_path = dir.join(&file_name);
-    if !archive_path.exists() {
-        return Err("未找到对应的备份文件".to_string());
-    }
+    let archive_path = backup_file_path(&game_id, &file_name)?;
 
     let note_path = archive_path.with_extension("txt");
     if remark.trim().is_empty() {
@@ -330,36 +1298,503 @@ pub fn update_backup_remark(
     fs::write(&note_path, remark).map_err(|e| format!("写入备注失败: {e}"))
 }
 
-/// 删除备份：将压缩包和同名备注（如存在）送回收站
-pub fn delete_backup(game_name: String, file_name: String) -> Result<(), String> {
-    let dir = backup_dir()?;
-    let filename_prefix = format!("{}-Backup", sanitize_filename(&game_name));
+/// 锁定或解除锁定指定备份：锁定状态存储在同名 .pinned 标记文件
+pub fn set_backup_pinned(game_id: String, file_name: String, pinned: bool) -> Result<(), String> {
+    let archive_path = backup_file_path(&game_id, &file_name)?;
 
-    if !file_name.starts_with(&filename_prefix) {
-        return Err("文件名与游戏不匹配".to_string());
+    let marker = pin_path(&archive_path);
+    if pinned {
+        fs::write(&marker, "").map_err(|e| format!("锁定备份失败: {e}"))
+    } else if marker.exists() {
+        fs::remove_file(&marker).map_err(|e| format!("解除锁定失败: {e}"))
+    } else {
+        Ok(())
     }
+}
 
-    let archive_path = dir.join(&file_name);
-    if !archive_path.exists() {
-        return Err("未找到对应的备份文件".to_string());
+/// 删除备份：将压缩包和同名备注、清单副本、锁定标记（如存在）送回收站
+/// 已锁定的备份需要 `force` 为 true 才能删除
+/// 删除去重快照时会一并清理存储中不再被引用的内容（这部分直接删除，从回收站还原快照也找不回来）
+pub fn delete_backup(game_id: String, file_name: String, force: bool) -> Result<(), String> {
+    let archive_path = backup_file_path(&game_id, &file_name)?;
+
+    let marker = pin_path(&archive_path);
+    if marker.exists() && !force {
+        return Err("备份已锁定，请先解除锁定或确认强制删除".to_string());
     }
 
     // 先删除压缩包
     trash::delete(&archive_path)
         .map_err(|e| format!("删除备份失败: {e}"))?;
 
-    // 再尝试删除备注（可选存在）
+    // 再尝试删除备注与清单副本（可选存在）
     let note_path = archive_path.with_extension("txt");
     if note_path.exists() {
         let _ = trash::delete(&note_path);
     }
+    let sidecar = manifest::sidecar_path(&archive_path);
+    if sidecar.exists() {
+        let _ = trash::delete(&sidecar);
+    }
+    if marker.exists() {
+        let _ = trash::delete(&marker);
+    }
+
+    // 去重快照引用的内容不在这里清理：快照只是进了回收站，用户还可能把它还原回来
+    // 需要回收空间时由用户显式执行 `collect_backup_garbage`
+    Ok(())
+}
+
+/// 清理指定游戏去重存储中不再被任何快照引用的内容，返回删除的数量
+/// 内容会被直接删除，之前删掉（在回收站里）的快照清理后就无法再复原
+pub fn collect_backup_garbage(game_id: &str) -> Result<usize, String> {
+    let dir = game_backup_dir(game_id)?;
+    if !dir.join("store").is_dir() {
+        return Ok(0);
+    }
+    let referenced = store::referenced_blobs(&dir)?;
+    BlobStore::open(&dir)?.collect_garbage(&referenced)
+}
+
+/// 收集指定游戏的备份目录与额外备份目录中以 `{游戏名}-` 开头的文件（压缩包、快照、备注、清单副本、锁定标记）
+fn game_backup_files(game_id: &str, game_name: &str) -> Result<Vec<PathBuf>, String> {
+    let prefix = format!("{}-", sanitize_filename(game_name));
+
+    let mut files = Vec::new();
+    for base in [game_backup_dir(game_id)?, game_extra_backup_dir(game_id)?] {
+        let entries = fs::read_dir(&base).map_err(|e| format!("读取备份目录失败: {e}"))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_file() {
+                continue;
+            }
+            let Some(name) = path.file_name().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            if name.starts_with(&prefix) {
+                files.push(path);
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// 是否为本程序生成的备份相关文件：压缩包、快照，以及同名的备注、清单副本与锁定标记
+fn is_backup_file(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|s| s.to_str()) else {
+        return false;
+    };
+    if !name.contains("-Backup-") && !name.contains("-ExtraBackup-") {
+        return false;
+    }
+    BackupFormat::from_path(path).is_some()
+        || name.ends_with(".txt")
+        || name.ends_with(".pinned")
+        || name.ends_with(".manifest.json")
+}
 
+/// 将指定游戏的备份与额外备份送回收站（含备注、清单副本、锁定标记与去重存储）
+/// 用于移除游戏时一并清理，锁定的备份也会被删除；目录中的其他文件不动，目录清空后才删除
+pub fn trash_game_backups(game_id: &str) -> Result<(), String> {
+    for dir in [game_backup_dir(game_id)?, game_extra_backup_dir(game_id)?] {
+        let entries = fs::read_dir(&dir).map_err(|e| format!("读取备份目录失败: {e}"))?;
+        let mut targets: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && is_backup_file(path))
+            .collect();
+        let store = dir.join("store");
+        if store.is_dir() {
+            targets.push(store);
+        }
+        if !targets.is_empty() {
+            trash::delete_all(&targets).map_err(|e| format!("删除备份失败: {e}"))?;
+        }
+        // 只删除空目录
+        let _ = fs::remove_dir(&dir);
+    }
     Ok(())
 }
 
-/// 复原备份：可选生成额外备份，移除原存档后解压备份文件
+/// 按相反顺序撤销已完成的改名（尽力而为）
+fn rollback_renames(done: &[(PathBuf, PathBuf)]) {
+    for (from, to) in done.iter().rev() {
+        let _ = fs::rename(to, from);
+    }
+}
+
+/// 重命名游戏：更新配置中的游戏名，并把备份、额外备份及其附属文件改为新名称前缀
+/// 备份按 id 目录归属，改文件名只是为了在文件夹里看着直观；任一步失败都会撤销已完成的改名，配置保持不变
+/// 压缩包内清单记录的游戏名保持原样，作为备份当时的历史信息
+pub fn rename_game(game_id: &str, new_name: &str) -> Result<config::AppConfig, String> {
+    let new_name = new_name.trim();
+    let old_name = config::check_game_rename(game_id, new_name)?;
+
+    let old_safe = sanitize_filename(&old_name);
+    let new_safe = sanitize_filename(new_name);
+    let mut done: Vec<(PathBuf, PathBuf)> = Vec::new();
+
+    // 过滤非法字符后文件名前缀相同（例如只改了冒号），不需要动文件
+    if old_safe != new_safe {
+        let old_prefix = format!("{old_safe}-");
+        for path in game_backup_files(game_id, &old_name)? {
+            let Some(name) = path.file_name().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            let Some(rest) = name.strip_prefix(&old_prefix) else {
+                continue;
+            };
+            let target = path.with_file_name(format!("{new_safe}-{rest}"));
+
+            // Windows 下 rename 会覆盖已存在的文件，先检查避免覆盖新名称下的旧备份
+            let result = if target.exists() {
+                Err(format!("目标文件已存在: {}", target.display()))
+            } else {
+                fs::rename(&path, &target).map_err(|e| format!("重命名备份 {name} 失败: {e}"))
+            };
+            if let Err(e) = result {
+                rollback_renames(&done);
+                return Err(e);
+            }
+            done.push((path, target));
+        }
+    }
+
+    config::rename_game_entry(game_id, new_name).inspect_err(|_| rollback_renames(&done))
+}
+
+/// 修改备份位置并移动已有备份：`game_id` 为空时修改全局的备份根目录（影响所有没有单独设置的游戏），
+/// 否则只修改该游戏；`location` 为空表示恢复默认位置
+/// 任一游戏移动失败都会把已移动的目录移回原处，配置保持不变
+pub fn set_backup_location(
+    game_id: Option<&str>,
+    location: Option<String>,
+) -> Result<config::AppConfig, String> {
+    let location = location
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty());
+    if location.as_ref().is_some_and(|l| !Path::new(l).is_absolute()) {
+        return Err("备份目录必须是绝对路径".to_string());
+    }
+
+    let config = config::read_config()?;
+    let mut updated = config.clone();
+    match game_id {
+        Some(id) => {
+            let Some(game) = updated.games.iter_mut().find(|g| g.id == id) else {
+                return Err("未找到对应的游戏配置".to_string());
+            };
+            game.backup_dir = location;
+        }
+        None => {
+            updated.settings.backup_root = location;
+            updated.settings.invalid.remove("backupRoot");
+        }
+    }
+
+    let mut moved: Vec<(PathBuf, PathBuf)> = Vec::new();
+    let rollback = |moved: &[(PathBuf, PathBuf)]| {
+        for (from, to) in moved.iter().rev() {
+            let _ = workdir::move_dir(to, from);
+        }
+    };
+    for (old_game, new_game) in config.games.iter().zip(&updated.games) {
+        let result = locate_game_backup_dir(&config, old_game).and_then(|old| {
+            let new = locate_game_backup_dir(&updated, new_game)?;
+            if old == new || !old.exists() {
+                return Ok(None);
+            }
+            if new.starts_with(&old) || old.starts_with(&new) {
+                return Err(format!("新旧备份目录不能互相包含: {}", new.display()));
+            }
+            workdir::move_dir(&old, &new).map(|_| Some((old, new)))
+        });
+        match result {
+            Ok(Some(pair)) => moved.push(pair),
+            Ok(None) => {}
+            Err(e) => {
+                rollback(&moved);
+                return Err(format!("移动 {} 的备份失败: {e}", old_game.name));
+            }
+        }
+    }
+
+    if let Err(e) = config::write_config(&updated) {
+        rollback(&moved);
+        return Err(e);
+    }
+    Ok(updated)
+}
+
+/// 工作目录下的标记文件：存在表示配置 v1 留下的旧备份还没有归位
+/// v1 -> v2 升级时创建，归位执行完后删除，之后不再扫描备份根目录
+const LEGACY_ADOPTION_MARKER: &str = "legacy-backups.pending";
+
+/// 旧备份归位的结果
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LegacyAdoption {
+    /// 移动到各游戏备份目录的备份数
+    pub adopted: usize,
+    /// 没能归位的备份及原因（文件留在原处，需要用户手动处理）
+    pub warnings: Vec<String>,
+}
+
+/// 最近一次旧备份归位的结果，前端启动时取走并提示用户
+static LAST_ADOPTION: Mutex<Option<LegacyAdoption>> = Mutex::new(None);
+
+/// 记下需要归位旧备份（标记与 config.json 放在同一目录）；在写入带 id 的配置之前调用，
+/// 写入后中途退出时下次启动仍会继续
+pub fn mark_legacy_backups_pending(config_path: &Path) -> Result<(), String> {
+    fs::write(config_path.with_file_name(LEGACY_ADOPTION_MARKER), b"")
+        .map_err(|e| format!("记录旧备份归位状态失败: {e}"))
+}
+
+/// 有待归位的旧备份时执行一次归位，完成后删除标记；结果留给前端通过 `take_legacy_adoption` 取走
+/// 归位失败不影响读取配置，只记录在结果中
+pub fn adopt_pending_legacy_backups(config: &config::AppConfig) {
+    let Ok(marker) = workdir::software_workdir().map(|w| w.join(LEGACY_ADOPTION_MARKER)) else {
+        return;
+    };
+    if !marker.exists() {
+        return;
+    }
+
+    // v1 没有自定义备份目录，旧备份只会在默认位置
+    let mut report = match default_backup_root() {
+        Ok(root) => {
+            let extra = extra_backup_dir();
+            let mut report = adopt_legacy_backups(config, &root, extra.as_deref().ok());
+            report.warnings.extend(extra.err());
+            report
+        }
+        Err(e) => LegacyAdoption {
+            adopted: 0,
+            warnings: vec![e],
+        },
+    };
+    if let Err(e) = fs::remove_file(&marker) {
+        report.warnings.push(format!("删除旧备份归位标记失败: {e}"));
+    }
+    if let Ok(mut last) = LAST_ADOPTION.lock() {
+        *last = Some(report);
+    }
+}
+
+/// 取走最近一次旧备份归位的结果（只会返回一次）
+pub fn take_legacy_adoption() -> Option<LegacyAdoption> {
+    LAST_ADOPTION.lock().ok()?.take()
+}
+
+/// 配置 v1 的旧备份归位：把默认备份目录根下按名称前缀关联的旧备份移动到各游戏的备份目录
+/// 过滤字符后多个游戏名相同时，优先按清单中记录的游戏名归属，仍无法区分时归给配置中靠前的游戏；
+/// 匹配不到任何游戏的文件留在原处
+/// 单个备份归位失败时记下原因并继续处理其他备份；目标位置已有同名文件时不覆盖
+fn adopt_legacy_backups(
+    config: &config::AppConfig,
+    root: &Path,
+    extra_root: Option<&Path>,
+) -> LegacyAdoption {
+    let mut report = LegacyAdoption {
+        adopted: 0,
+        warnings: Vec::new(),
+    };
+    let legacy_store = root.join("store");
+    let mut sources = vec![(root, "-Backup-", false)];
+    sources.extend(extra_root.map(|dir| (dir, "-ExtraBackup-", true)));
+
+    for (base, marker, is_extra) in sources {
+        let entries = match fs::read_dir(base) {
+            Ok(entries) => entries,
+            Err(e) => {
+                report.warnings.push(format!("读取备份目录 {} 失败: {e}", base.display()));
+                continue;
+            }
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_file() || BackupFormat::from_path(&path).is_none() {
+                continue;
+            }
+            let Some(name) = path.file_name().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            let Some((prefix, _)) = name.rsplit_once(marker) else {
+                continue;
+            };
+
+            let candidates: Vec<&GameEntry> = config
+                .games
+                .iter()
+                .filter(|g| sanitize_filename(&g.name) == prefix)
+                .collect();
+            let owner = if candidates.len() > 1 {
+                let recorded = read_backup_manifest(&path).map(|m| m.game_name);
+                candidates
+                    .iter()
+                    .find(|g| Some(&g.name) == recorded.as_ref())
+                    .or(candidates.first())
+            } else {
+                candidates.first()
+            };
+            let Some(owner) = owner else {
+                continue;
+            };
+
+            match adopt_legacy_backup(config, owner, &path, is_extra, root) {
+                Ok(true) => report.adopted += 1,
+                Ok(false) => {}
+                Err(e) => report.warnings.push(format!("{name}: {e}")),
+            }
+        }
+    }
+
+    // 旧存储只保留仍留在根目录的快照引用的内容；有读不出来的快照时不清理
+    if legacy_store.is_dir() {
+        let result = store::referenced_blobs(root)
+            .and_then(|referenced| BlobStore::open(root)?.collect_garbage(&referenced));
+        if let Err(e) = result {
+            report.warnings.push(format!("清理旧的去重存储失败: {e}"));
+        }
+    }
+    report
+}
+
+/// 把一份旧备份（连同备注、锁定标记与清单副本）移动到所属游戏的备份目录；目标已存在时跳过，返回是否移动
+fn adopt_legacy_backup(
+    config: &config::AppConfig,
+    owner: &GameEntry,
+    path: &Path,
+    is_extra: bool,
+    root: &Path,
+) -> Result<bool, String> {
+    let dest_dir = if is_extra {
+        game_extra_backup_dir(&owner.id)?
+    } else {
+        let dir = locate_game_backup_dir(config, owner)?;
+        fs::create_dir_all(&dir).map_err(|e| format!("创建备份目录失败: {e}"))?;
+        dir
+    };
+    let Some(name) = path.file_name() else {
+        return Ok(false);
+    };
+    if dest_dir.join(name).exists() {
+        return Ok(false);
+    }
+
+    // 快照引用的内容复制到新目录的存储里，旧存储稍后统一清理
+    if BackupFormat::from_path(path) == Some(BackupFormat::Snapshot) && root.join("store").is_dir() {
+        let old_store = BlobStore::open(root)?;
+        let new_store = BlobStore::open(&dest_dir)?;
+        for file in store::read_snapshot(path)?.files {
+            new_store.copy_blob_from(&old_store, &file.sha256)?;
+        }
+    }
+
+    let companions = [
+        path.with_extension("txt"),
+        pin_path(path),
+        manifest::sidecar_path(path),
+    ];
+    for file in companions.iter().map(PathBuf::as_path).filter(|p| p.is_file()).chain([path]) {
+        let Some(file_name) = file.file_name() else {
+            continue;
+        };
+        // 上次中断时可能已经移过去的附属文件
+        let dest = dest_dir.join(file_name);
+        if dest.exists() {
+            continue;
+        }
+        fs::rename(file, dest).map_err(|e| format!("移动到 {} 失败: {e}", dest_dir.display()))?;
+    }
+    Ok(true)
+}
+
+/// 复原的目标存档路径：单账号备份只有一个（账号为 None），多账号备份按压缩包中的账号逐个解析
+fn restore_targets(
+    path_template: &str,
+    steam_uid: Option<String>,
+    accounts: &[String],
+    app_id: Option<u32>,
+) -> Result<Vec<(Option<String>, PathBuf)>, String> {
+    if accounts.is_empty() {
+        let target = resolve_template_path(path_template.to_string(), steam_uid, app_id)?;
+        return Ok(vec![(None, target)]);
+    }
+    if !path_template.contains("{SteamUID}") {
+        return Err("这是多账号备份，但存档路径中没有 {SteamUID}，无法复原".to_string());
+    }
+    accounts
+        .iter()
+        .map(|uid| {
+            if uid.is_empty() || !uid.chars().all(|c| c.is_ascii_digit()) {
+                return Err(format!("备份清单中的账号 id 无效: {uid}"));
+            }
+            let target =
+                resolve_template_path(path_template.to_string(), Some(uid.clone()), app_id)?;
+            Ok((Some(uid.clone()), target))
+        })
+        .collect()
+}
+
+/// 把解压好的目录换到存档位置：先把所有原存档改名让位，再把解压好的目录改名过去；
+/// 同一父目录下的 rename 不会出现半成品，任一步失败都会撤销已完成的改名
+/// 成功后把旧存档移入回收站，返回未能移入回收站的旧存档目录（需要用户手动处理）
+fn swap_into_place(plans: &[(PathBuf, PathBuf)], ts_tag: &str) -> Result<Vec<String>, String> {
+    let mut moved_aside: Vec<(PathBuf, PathBuf)> = Vec::new();
+    for (_, target) in plans {
+        if !target.exists() {
+            continue;
+        }
+        let result = restore_sibling(target, "previous", ts_tag).and_then(|previous| {
+            fs::rename(target, &previous)
+                .map(|_| previous)
+                .map_err(|e| format!("移动原存档失败（原存档未改动）: {e}"))
+        });
+        match result {
+            Ok(previous) => moved_aside.push((target.clone(), previous)),
+            Err(e) => {
+                rollback_renames(&moved_aside);
+                return Err(e);
+            }
+        }
+    }
+
+    let mut placed: Vec<(PathBuf, PathBuf)> = Vec::new();
+    for (staged, target) in plans {
+        let result = fs::create_dir_all(staged)
+            .and_then(|_| match target.parent() {
+                Some(parent) => fs::create_dir_all(parent),
+                None => Ok(()),
+            })
+            .and_then(|_| fs::rename(staged, target));
+        if let Err(e) = result {
+            rollback_renames(&placed);
+            let mut msg = format!("替换存档目录失败: {e}");
+            for (target, previous) in moved_aside.iter().rev() {
+                if fs::rename(previous, target).is_err() {
+                    msg = format!(
+                        "{msg}；原存档暂存在 {}，下次备份或复原时会自动移回",
+                        previous.to_string_lossy()
+                    );
+                }
+            }
+            return Err(msg);
+        }
+        placed.push((staged.clone(), target.clone()));
+    }
+
+    // 旧存档移入回收站；此时复原已经完成，失败时只提示用户手动处理
+    Ok(moved_aside
+        .into_iter()
+        .filter(|(_, previous)| trash::delete(previous).is_err())
+        .map(|(_, previous)| previous.to_string_lossy().to_string())
+        .collect())
+}
+
+/// 复原备份：可选生成额外备份，解压到同级临时目录并校验，再用 rename 替换原存档
 pub fn restore_backup(
-    game_name: String,
+    game_id: String,
     path_template: String,
     backup_path: String,
     steam_uid: Option<String>,
@@ -369,38 +1804,97 @@ pub fn restore_backup(
         return Err(stage_err(RestoreStage::Check, "备份文件不存在"));
     }
 
-    // 目前仅支持 zip 解压，7z 需要另行实现
-    if let Some(ext) = backup_file.extension().and_then(|s| s.to_str()) {
-        if !ext.eq_ignore_ascii_case("zip") {
-            return Err(stage_err(RestoreStage::Check, "当前仅支持 .zip 备份文件"));
-        }
-    } else {
+    // 支持 .zip 与 .7z（不区分大小写）
+    if BackupFormat::from_path(&backup_file).is_none() {
         return Err(stage_err(RestoreStage::Check, "无法识别的备份文件扩展名"));
     }
 
-    let target_path = resolve_template_path(path_template, steam_uid)
-        .map_err(|e| stage_err(RestoreStage::Check, e))?;
+    // 预检压缩包条目：存在越界条目则在动原存档之前直接拒绝
+    let rejected = inspect_archive(&backup_file).map_err(|e| stage_err(RestoreStage::Check, e))?;
+    if rejected.iter().any(|r| r.escapes) {
+        let detail = rejected
+            .iter()
+            .map(|r| format!("{}（{}）", r.name, r.reason))
+            .collect::<Vec<_>>()
+            .join("; ");
+        return Err(stage_err(
+            RestoreStage::Check,
+            format!("备份文件包含不安全的条目，已拒绝复原: {detail}"),
+        ));
+    }
 
     // 读取设置，决定是否额外备份
     let config_snapshot = config::read_config()
         .map_err(|e| stage_err(RestoreStage::Check, e))?;
-    let extra_backup_enabled = config_snapshot
-        .settings
-        .get("restoreExtraBackup")
-        .and_then(|v| v.as_bool())
-        .unwrap_or(true);
+    let game = config::find_game(&config_snapshot, &game_id)
+        .map_err(|e| stage_err(RestoreStage::Check, e))?;
+    let game_name = game.name.clone();
+
+    // 多账号备份：每个账号的存档复原到各自的存档路径
+    let accounts: Vec<String> = read_backup_manifest(&backup_file)
+        .map(|m| m.accounts.into_iter().map(|a| a.steam_uid).collect())
+        .unwrap_or_default();
+    let targets = restore_targets(
+        &path_template,
+        steam_uid.clone(),
+        &accounts,
+        game.steam_app_id,
+    )
+    .map_err(|e| stage_err(RestoreStage::Check, e))?;
+    for (_, target) in &targets {
+        recover_interrupted_restore(target).map_err(|e| stage_err(RestoreStage::Check, e))?;
+    }
+    let extra_backup_enabled = config_snapshot.settings.restore_extra_backup;
+    // 额外备份放在 extra-backup，不参与去重存储的引用统计，因此去重模式下改用 zip
+    let format = match config_snapshot.settings.backup_format {
+        BackupFormat::Snapshot => BackupFormat::Zip,
+        f => f,
+    };
 
     let mut extra_backup_path: Option<PathBuf> = None;
 
-    // 生成额外备份（仅当配置开启且目标目录存在时执行）
-    if extra_backup_enabled && target_path.exists() {
-        let dir = extra_backup_dir().map_err(|e| stage_err(RestoreStage::ExtraBackup, e))?;
+    // 生成额外备份（仅当配置开启且目标目录存在时执行），多账号时沿用压缩包中的账号目录结构
+    let existing: Vec<ArchiveSource> = targets
+        .iter()
+        .filter(|(_, target)| target.exists())
+        .map(|(uid, target)| match uid {
+            Some(uid) => ArchiveSource::account(uid, target),
+            None => ArchiveSource::root(target),
+        })
+        .collect();
+    if extra_backup_enabled && !existing.is_empty() {
+        let dir = game_extra_backup_dir(&game_id).map_err(|e| stage_err(RestoreStage::ExtraBackup, e))?;
         let safe_name = sanitize_filename(&game_name);
-        let (ts_tag, _) = now_timestamp();
+        let (ts_tag, ts_millis) = now_timestamp();
         let stem = format!("{safe_name}-ExtraBackup-{ts_tag}");
-        let archive_path = dir.join(format!("{stem}.zip"));
-
-        zip_directory(&target_path, &archive_path)
+        let archive_path = dir.join(format!("{stem}.{}", format.extension()));
+
+        let mut manifest = if accounts.is_empty() {
+            BackupManifest::new(
+                &game_name,
+                &targets[0].1,
+                steam_uid,
+                config_snapshot.version,
+                ts_millis,
+            )
+        } else {
+            let mut manifest = BackupManifest::new(
+                &game_name,
+                Path::new(&path_template),
+                None,
+                config_snapshot.version,
+                ts_millis,
+            );
+            manifest.accounts = existing
+                .iter()
+                .map(|source| AccountSource {
+                    steam_uid: source.prefix.clone(),
+                    source_path: source.dir.to_string_lossy().to_string(),
+                })
+                .collect();
+            manifest
+        };
+        archive_directory(&existing, &archive_path, format, &mut manifest)
             .map_err(|e| stage_err(RestoreStage::ExtraBackup, e))?;
 
         // 顺便写一份简短的说明，便于用户识别
@@ -413,32 +1907,41 @@ pub fn restore_backup(
         extra_backup_path = Some(archive_path);
     }
 
-    // 将原存档移入回收站（避免误删）
-    if target_path.exists() {
-        trash::delete(&target_path).map_err(|e| {
-            stage_err(
-                RestoreStage::Delete,
-                format!("将原存档移入回收站失败: {e}"),
-            )
+    let (ts_tag, _) = now_timestamp();
+    let staging_path = restore_sibling(&targets[0].1, "staging", &ts_tag)
+        .map_err(|e| stage_err(RestoreStage::Extract, e))?;
+
+    // 先解压到同级的临时目录，原存档在替换之前保持不动
+    let summary = fs::create_dir_all(&staging_path)
+        .map_err(|e| format!("创建临时目录失败: {e}"))
+        .and_then(|_| extract_archive(&backup_file, &staging_path))
+        .map_err(|e| {
+            let _ = fs::remove_dir_all(&staging_path);
+            stage_err(RestoreStage::Extract, e)
         })?;
-    }
 
-    // 确保目标目录存在
-    fs::create_dir_all(&target_path)
-        .map_err(|e| stage_err(RestoreStage::Extract, format!("创建目标目录失败: {e}")))?;
-
-    // 解压备份；失败时尝试用额外备份回滚
-    if let Err(e) = unzip_directory(&backup_file, &target_path) {
-        // 清理可能的半成品
-        let _ = fs::remove_dir_all(&target_path);
-
-        if let Some(extra) = &extra_backup_path {
-            let _ = fs::create_dir_all(&target_path);
-            let _ = unzip_directory(extra, &target_path);
-        }
+    // 校验解压结果：文件数与总大小需要与压缩包记录一致
+    if let Err(e) = verify_extracted(&staging_path, &summary) {
+        let _ = fs::remove_dir_all(&staging_path);
+        return Err(stage_err(RestoreStage::Verify, e));
+    }
 
-        return Err(stage_err(RestoreStage::Extract, e));
+    // 每个存档路径对应临时目录中的一个目录：单账号时为临时目录本身，多账号时为其中的账号目录
+    let plans: Vec<(PathBuf, PathBuf)> = targets
+        .iter()
+        .map(|(uid, target)| match uid {
+            Some(uid) => (staging_path.join(uid), target.clone()),
+            None => (staging_path.clone(), target.clone()),
+        })
+        .collect();
+    let stale = swap_into_place(&plans, &ts_tag).map_err(|e| {
+        let _ = fs::remove_dir_all(&staging_path);
+        stage_err(RestoreStage::Swap, e)
+    });
+    if !accounts.is_empty() {
+        let _ = fs::remove_dir_all(&staging_path);
     }
+    let stale = stale?;
 
     let ts = parse_timestamp_from_name(
         backup_file
@@ -449,14 +1952,254 @@ pub fn restore_backup(
     .or_else(|| file_modified_millis(&backup_file))
     .unwrap_or_else(|| chrono::Local::now().timestamp_millis());
 
-    let config = config::update_last_save(&game_name, ts)
+    let config = config::update_last_save(&game_id, ts)
         .map_err(|e| stage_err(RestoreStage::UpdateConfig, e))?;
 
     Ok(RestoreResponse {
         config,
-        restored_path: target_path.to_string_lossy().to_string(),
+        restored_path: targets
+            .iter()
+            .map(|(_, target)| target.to_string_lossy().to_string())
+            .collect::<Vec<_>>()
+            .join("；"),
+        steam_uids: accounts,
         backup_file: backup_file.to_string_lossy().to_string(),
         extra_backup_path: extra_backup_path.map(|p| p.to_string_lossy().to_string()),
         timestamp: ts,
+        rejected_entries: summary.rejected,
+        stale_save_path: (!stale.is_empty()).then(|| stale.join("；")),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 读取进程的内存峰值（VmHWM，KiB）；非 Linux 平台返回 None
+    fn peak_rss_kib() -> Option<u64> {
+        let status = fs::read_to_string("/proc/self/status").ok()?;
+        let line = status.lines().find(|l| l.starts_with("VmHWM:"))?;
+        line.split_whitespace().nth(1)?.parse().ok()
+    }
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("game-sl-test-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn rejected(name: &str) -> RejectedEntry {
+        sanitize_entry_name(name).expect_err(name)
+    }
+
+    #[test]
+    fn sanitize_entry_name_rejects_escaping_paths() {
+        for name in [
+            "../x",
+            "a/../../x",
+            ".. /x",
+            "a\\..\\..\\x",
+            "C:x",
+            "/abs",
+            "\\\\server\\share",
+        ] {
+            assert!(rejected(name).escapes, "{name} 应视为越界");
+        }
+        assert_eq!(
+            sanitize_entry_name("a/./b\\c.sav").ok(),
+            Some(PathBuf::from("a").join("b").join("c.sav"))
+        );
+    }
+
+    #[test]
+    fn sanitize_entry_name_rejects_device_names() {
+        for name in ["NUL.txt", "com1", "save/LPT9.dat", "aux .sav"] {
+            let r = rejected(name);
+            assert!(!r.escapes, "{name} 不会越界，只是跳过");
+            assert_eq!(r.reason, "Windows 设备名");
+        }
+        assert!(sanitize_entry_name("console.sav").is_ok());
+    }
+
+    #[test]
+    fn check_zip_entry_rejects_symlinks() {
+        let root = scratch_dir("zip-symlink");
+        let path = root.join("link.zip");
+        let mut zip = ZipWriter::new(File::create(&path).unwrap());
+        let options = FileOptions::default();
+        zip.add_symlink("save/link", "/etc/passwd", options)
+            .unwrap();
+        zip.start_file("save/ok.sav", options).unwrap();
+        zip.write_all(b"ok").unwrap();
+        zip.finish().unwrap();
+
+        let rejected = inspect_archive(&path).unwrap();
+        assert_eq!(rejected.len(), 1);
+        assert_eq!(rejected[0].name, "save/link");
+        assert_eq!(rejected[0].reason, "符号链接");
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    /// 含越界条目的压缩包在检查阶段就被拒绝：不会生成额外备份，也不会动原存档
+    #[test]
+    fn restore_backup_refuses_hostile_zip() {
+        let root = scratch_dir("hostile-zip");
+        let save = root.join("save");
+        fs::create_dir_all(&save).unwrap();
+        fs::write(save.join("slot.sav"), b"original").unwrap();
+
+        let path = root.join("evil-Backup-20250101-120000.zip");
+        let mut zip = ZipWriter::new(File::create(&path).unwrap());
+        let options = FileOptions::default();
+        zip.start_file("../evil.sav", options).unwrap();
+        zip.write_all(b"evil").unwrap();
+        zip.finish().unwrap();
+
+        let Err(err) = restore_backup(
+            "missing-game".to_string(),
+            save.to_string_lossy().to_string(),
+            path.to_string_lossy().to_string(),
+            None,
+        ) else {
+            panic!("含越界条目的备份不应被复原");
+        };
+        assert!(err.starts_with("[CHECK]"), "{err}");
+        assert!(err.contains("../evil.sav"), "{err}");
+        assert_eq!(fs::read(save.join("slot.sav")).unwrap(), b"original");
+        assert!(!root.join("evil.sav").exists());
+        assert_eq!(fs::read_dir(&root).unwrap().count(), 2);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    /// 旧备份归位：单个备份失败时记下原因并继续，失败的文件留在原处
+    #[test]
+    fn adopt_legacy_backups_skips_failing_entries() {
+        let root = scratch_dir("adopt");
+        let legacy = root.join("legacy");
+        fs::create_dir_all(legacy.join("store")).unwrap();
+        fs::write(legacy.join("Hollow-Backup-20250101-120000.zip"), b"zip").unwrap();
+        fs::write(legacy.join("Hollow-Backup-20250101-120000.txt"), b"remark").unwrap();
+        fs::write(legacy.join("Hollow-Backup-20250102-120000.snapshot"), b"not json").unwrap();
+        fs::write(legacy.join("Bad-Backup-20250101-120000.zip"), b"zip").unwrap();
+        fs::write(legacy.join("Nobody-Backup-20250101-120000.zip"), b"zip").unwrap();
+
+        let config: config::AppConfig = serde_json::from_value(serde_json::json!({
+            "version": 2,
+            "settings": { "backupRoot": root.join("new") },
+            "games": [
+                { "id": "g1", "name": "Hollow", "path": "", "icon": "" },
+                { "id": "../g2", "name": "Bad", "path": "", "icon": "" },
+            ],
+        }))
+        .unwrap();
+        let report = adopt_legacy_backups(&config, &legacy, None);
+
+        // 读不出来的快照、无效的游戏 id、快照读不出来时不清理旧存储
+        assert_eq!(report.adopted, 1);
+        assert_eq!(report.warnings.len(), 3, "{:?}", report.warnings);
+        let dest = root.join("new").join("g1");
+        assert!(dest.join("Hollow-Backup-20250101-120000.zip").is_file());
+        assert!(dest.join("Hollow-Backup-20250101-120000.txt").is_file());
+        for name in [
+            "Hollow-Backup-20250102-120000.snapshot",
+            "Bad-Backup-20250101-120000.zip",
+            "Nobody-Backup-20250101-120000.zip",
+        ] {
+            assert!(legacy.join(name).is_file(), "{name} 应留在原处");
+        }
+
+        assert_eq!(adopt_legacy_backups(&config, &legacy, None).adopted, 0);
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    /// 三种格式复原后都保留空目录（去重快照靠清单中的 `dirs`）
+    #[test]
+    fn empty_directories_survive_every_format() {
+        let root = scratch_dir("empty-dirs");
+        let src = root.join("save");
+        fs::create_dir_all(src.join("empty")).unwrap();
+        fs::create_dir_all(src.join("slot")).unwrap();
+        fs::write(src.join("slot").join("a.sav"), b"a").unwrap();
+
+        for format in [BackupFormat::Zip, BackupFormat::SevenZ, BackupFormat::Snapshot] {
+            let dest = root.join(format!("save-Backup-20250101-120000.{}", format.extension()));
+            let mut manifest = BackupManifest::new("test", &src, None, 1, 0);
+            archive_directory(&[ArchiveSource::root(&src)], &dest, format, &mut manifest).unwrap();
+
+            let out = root.join(format!("restored-{}", format.extension()));
+            let summary = extract_archive(&dest, &out).unwrap();
+            assert_eq!(summary.files, 1, "{format:?}");
+            assert!(out.join("empty").is_dir(), "{format:?} 丢失了空目录");
+            assert_eq!(fs::read(out.join("slot").join("a.sav")).unwrap(), b"a");
+        }
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    /// 条目大小达到阈值时写出 zip64 扩展字段（头部 id 0x0001），差一个字节时不写
+    #[test]
+    fn zip_entry_options_enable_zip64_at_threshold() {
+        let root = scratch_dir("zip64");
+        for (size, expected) in [(ZIP64_THRESHOLD - 1, false), (ZIP64_THRESHOLD, true)] {
+            let path = root.join(format!("{size}.zip"));
+            let mut zip = ZipWriter::new(File::create(&path).unwrap());
+            let options = FileOptions::default().compression_method(CompressionMethod::Stored);
+            zip.start_file("a.sav", zip_entry_options(options, size))
+                .unwrap();
+            zip.write_all(b"a").unwrap();
+            zip.finish().unwrap();
+
+            // 本地文件头：固定 30 字节，之后是文件名与扩展字段
+            let bytes = fs::read(&path).unwrap();
+            let name_len = u16::from_le_bytes([bytes[26], bytes[27]]) as usize;
+            let extra_len = u16::from_le_bytes([bytes[28], bytes[29]]) as usize;
+            let extra = &bytes[30 + name_len..30 + name_len + extra_len];
+            let has_zip64 = extra.len() >= 2 && extra[..2] == [0x01, 0x00];
+            assert_eq!(has_zip64, expected, "大小 {size}");
+        }
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    /// 回归测试：压缩大文件时不应把整个文件读进内存
+    /// 需要压缩 256 MiB 且依赖进程级的内存峰值，手动运行：`cargo test -- --ignored`
+    #[test]
+    #[ignore = "压缩 256 MiB，耗时较长，且内存峰值会受并行测试影响"]
+    fn zip_directory_streams_large_files() {
+        const FILE_SIZE: u64 = 256 * 1024 * 1024;
+        let root = scratch_dir("zip-stream");
+        let src = root.join("save");
+        fs::create_dir_all(src.join("slot")).unwrap();
+
+        // 稀疏文件：占用磁盘很少，读取时却是完整的 256 MiB
+        let big = File::create(src.join("slot").join("big.sav")).unwrap();
+        big.set_len(FILE_SIZE).unwrap();
+        fs::write(src.join("small.sav"), b"hello").unwrap();
+
+        let before = peak_rss_kib();
+        let dest = root.join("out.zip");
+        let mut manifest = BackupManifest::new("test", &src, None, 1, 0);
+        zip_directory(&[ArchiveSource::root(&src)], &dest, &mut manifest).unwrap();
+        let after = peak_rss_kib();
+
+        if let (Some(before), Some(after)) = (before, after) {
+            let grown_mib = after.saturating_sub(before) / 1024;
+            assert!(grown_mib < 64, "压缩时内存峰值增长了 {grown_mib} MiB");
+        }
+
+        let out = root.join("restored");
+        let summary = unzip_directory(&dest, &out).unwrap();
+        assert_eq!(summary.files, 2);
+        assert_eq!(summary.bytes, FILE_SIZE + 5);
+        assert_eq!(manifest.files.len(), 2);
+        assert_eq!(
+            read_embedded_manifest(&dest).map(|m| m.summary().total_size),
+            Some(FILE_SIZE + 5)
+        );
+        verify_extracted(&out, &summary).unwrap();
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,346 @@
+use crate::backup::check_game_id;
+use crate::config::{self, validate_game, AppConfig, GameDraft, GameEntry};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// 导出文件的格式版本
+const CATALOG_VERSION: u32 = 1;
+
+/// 导出的游戏清单：只包含可以分享的游戏定义，不含存档时间、备份目录等本机信息
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GameCatalog {
+    pub version: u32,
+    pub games: Vec<CatalogGame>,
+}
+
+/// 清单中的一个游戏；`id` 用于导入时与本地游戏对应，手写的清单可以省略
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CatalogGame {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(flatten)]
+    pub game: GameDraft,
+}
+
+/// 未能导入的游戏及原因
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportConflict {
+    pub name: String,
+    /// 发生冲突的本地游戏名称（字段校验失败时为空）
+    pub existing: Option<String>,
+    pub reason: String,
+}
+
+/// 导入结果：各列表中为游戏名称
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+    pub unchanged: Vec<String>,
+    pub conflicts: Vec<ImportConflict>,
+    pub config: AppConfig,
+}
+
+/// 本地游戏与清单中的定义是否一致（不比较名称）
+fn same_definition(local: &GameEntry, game: &GameDraft) -> bool {
+    local.path == game.path
+        && local.icon == game.icon
+        && local.kind == game.kind
+        && local.steam_app_id == game.steam_app_id
+}
+
+/// 导出指定游戏（`game_ids` 为空时导出全部）到 JSON 文件，返回导出的数量
+pub fn export_games(game_ids: &[String], file: &Path) -> Result<usize, String> {
+    let catalog = build_catalog(&config::read_config()?, game_ids)?;
+    let content =
+        serde_json::to_string_pretty(&catalog).map_err(|e| format!("序列化游戏清单失败: {e}"))?;
+    fs::write(file, content).map_err(|e| format!("写入游戏清单失败: {e}"))?;
+    Ok(catalog.games.len())
+}
+
+/// 从配置生成游戏清单（`game_ids` 为空时包含全部游戏）
+fn build_catalog(config: &AppConfig, game_ids: &[String]) -> Result<GameCatalog, String> {
+    let games: Vec<&GameEntry> = if game_ids.is_empty() {
+        config.games.iter().collect()
+    } else {
+        game_ids
+            .iter()
+            .map(|id| config::find_game(config, id))
+            .collect::<Result<_, _>>()?
+    };
+
+    let catalog = GameCatalog {
+        version: CATALOG_VERSION,
+        games: games
+            .iter()
+            .map(|g| CatalogGame {
+                id: Some(g.id.clone()),
+                game: GameDraft {
+                    name: g.name.clone(),
+                    path: g.path.clone(),
+                    icon: g.icon.clone(),
+                    kind: g.kind.clone(),
+                    steam_app_id: g.steam_app_id,
+                },
+            })
+            .collect(),
+    };
+    Ok(catalog)
+}
+
+/// 从 JSON 文件导入游戏定义，按 id、其次按名称与本地游戏合并：
+/// - 没有对应的本地游戏：新增（清单里的 id 可用时沿用，便于之后再次同步）
+/// - 同 id 且内容不同：`overwrite` 为 true 时更新路径、图标、类型与 Steam App ID，否则记为冲突；
+///   名称始终保留本地的（改名会涉及备份文件，请单独改名）
+/// - 同名但 id 不同：记为冲突
+pub fn import_games(file: &Path, overwrite: bool) -> Result<ImportReport, String> {
+    let content = fs::read_to_string(file).map_err(|e| format!("读取游戏清单失败: {e}"))?;
+    let catalog: GameCatalog =
+        serde_json::from_str(&content).map_err(|e| format!("解析游戏清单失败: {e}"))?;
+    if catalog.version > CATALOG_VERSION {
+        return Err(format!(
+            "游戏清单版本为 {}，高于当前程序支持的版本 {CATALOG_VERSION}，请升级程序后再导入",
+            catalog.version
+        ));
+    }
+
+    merge_games(catalog.games, overwrite)
+}
+
+/// 把一组游戏定义合并进配置，规则见 `import_games`；有新增或更新时写回配置
+pub fn merge_games(games: Vec<CatalogGame>, overwrite: bool) -> Result<ImportReport, String> {
+    let report = merge_into(config::read_config()?, games, overwrite);
+    if !report.added.is_empty() || !report.updated.is_empty() {
+        config::write_config(&report.config)?;
+    }
+    Ok(report)
+}
+
+/// 在内存中合并，返回的报告中带着合并后的配置（不写入文件）
+fn merge_into(mut config: AppConfig, games: Vec<CatalogGame>, overwrite: bool) -> ImportReport {
+    let mut added = Vec::new();
+    let mut updated = Vec::new();
+    let mut unchanged = Vec::new();
+    let mut conflicts = Vec::new();
+
+    for item in games {
+        let mut game = item.game;
+        game.name = game.name.trim().to_string();
+        let by_id = item
+            .id
+            .as_deref()
+            .and_then(|id| config.games.iter().position(|g| g.id == id));
+
+        if let Some(index) = by_id {
+            let local = &config.games[index];
+            if same_definition(local, &game) {
+                unchanged.push(local.name.clone());
+                continue;
+            }
+            if !overwrite {
+                conflicts.push(ImportConflict {
+                    name: game.name,
+                    existing: Some(local.name.clone()),
+                    reason: "本地已有同 id 的游戏，且定义不同".to_string(),
+                });
+                continue;
+            }
+            let draft = GameDraft {
+                name: local.name.clone(),
+                ..game
+            };
+            if let Err(reason) = validate_game(&config, &draft, Some(&local.id)) {
+                conflicts.push(ImportConflict {
+                    name: draft.name,
+                    existing: None,
+                    reason,
+                });
+                continue;
+            }
+            let local = &mut config.games[index];
+            local.path = draft.path;
+            local.icon = draft.icon;
+            local.kind = draft.kind;
+            local.steam_app_id = draft.steam_app_id;
+            updated.push(local.name.clone());
+        } else if let Some(local) = config.games.iter().find(|g| g.name == game.name) {
+            if same_definition(local, &game) {
+                unchanged.push(local.name.clone());
+            } else {
+                conflicts.push(ImportConflict {
+                    name: game.name,
+                    existing: Some(local.name.clone()),
+                    reason: "已存在同名游戏，且定义不同".to_string(),
+                });
+            }
+        } else {
+            if let Err(reason) = validate_game(&config, &game, None) {
+                conflicts.push(ImportConflict {
+                    name: game.name,
+                    existing: None,
+                    reason,
+                });
+                continue;
+            }
+            let id = match item.id {
+                Some(id) if check_game_id(&id).is_ok() => id,
+                _ => config::new_game_id(|id| config.games.iter().any(|g| g.id == id)),
+            };
+            added.push(game.name.clone());
+            config.games.push(GameEntry {
+                id,
+                name: game.name,
+                path: game.path,
+                icon: game.icon,
+                last_save: None,
+                kind: game.kind,
+                steam_app_id: game.steam_app_id,
+                retention: None,
+                backup_dir: None,
+            });
+        }
+    }
+
+    ImportReport {
+        added,
+        updated,
+        unchanged,
+        conflicts,
+        config,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn local_config() -> AppConfig {
+        serde_json::from_value(json!({
+            "version": 2,
+            "games": [
+                { "id": "ga", "name": "A", "path": "{Home}\\a", "icon": "", "type": "userdata" },
+                { "id": "gb", "name": "B", "path": "{Home}\\b", "icon": "", "type": "userdata" },
+            ],
+        }))
+        .unwrap()
+    }
+
+    fn item(id: Option<&str>, name: &str, path: &str) -> CatalogGame {
+        CatalogGame {
+            id: id.map(str::to_string),
+            game: GameDraft {
+                name: name.to_string(),
+                path: path.to_string(),
+                icon: String::new(),
+                kind: Some("userdata".to_string()),
+                steam_app_id: None,
+            },
+        }
+    }
+
+    #[test]
+    fn same_id_with_different_definition() {
+        let games = || vec![item(Some("ga"), "A renamed", "{Home}\\a2")];
+
+        let report = merge_into(local_config(), games(), false);
+        assert!(report.updated.is_empty());
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(report.conflicts[0].existing.as_deref(), Some("A"));
+        assert_eq!(report.config.games[0].path, "{Home}\\a");
+
+        // 覆盖时只更新定义，名称保留本地的
+        let report = merge_into(local_config(), games(), true);
+        assert_eq!(report.updated, ["A"]);
+        assert!(report.conflicts.is_empty());
+        assert_eq!(report.config.games[0].name, "A");
+        assert_eq!(report.config.games[0].path, "{Home}\\a2");
+    }
+
+    #[test]
+    fn same_id_with_invalid_definition_is_rejected_on_overwrite() {
+        let report = merge_into(
+            local_config(),
+            vec![item(Some("ga"), "A", "{Bogus}\\a")],
+            true,
+        );
+        assert!(report.updated.is_empty());
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(report.conflicts[0].existing, None);
+        assert_eq!(report.config.games[0].path, "{Home}\\a");
+    }
+
+    #[test]
+    fn same_name_with_different_id() {
+        let games = vec![
+            item(Some("gx"), "B", "{Home}\\other"),
+            item(Some("gy"), "B", "{Home}\\b"),
+        ];
+        let report = merge_into(local_config(), games, true);
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(report.conflicts[0].existing.as_deref(), Some("B"));
+        assert_eq!(report.unchanged, ["B"]);
+        assert_eq!(report.config.games.len(), 2);
+    }
+
+    #[test]
+    fn new_games_keep_valid_ids_only() {
+        let games = vec![
+            item(Some("gc"), "C", "{Home}\\c"),
+            item(Some("../escape"), "D", "{Home}\\d"),
+            item(None, "E", "{Home}\\e"),
+            item(Some("gf"), "F", "{Bogus}\\f"),
+        ];
+        let report = merge_into(local_config(), games, false);
+        assert_eq!(report.added, ["C", "D", "E"]);
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(report.conflicts[0].name, "F");
+
+        let ids: Vec<&str> = report.config.games.iter().map(|g| g.id.as_str()).collect();
+        assert_eq!(ids[2], "gc");
+        assert!(
+            ids[3] != "../escape" && check_game_id(ids[3]).is_ok(),
+            "{ids:?}"
+        );
+        assert!(check_game_id(ids[4]).is_ok());
+        assert_ne!(ids[3], ids[4]);
+    }
+
+    #[test]
+    fn export_then_import_round_trip() {
+        let local = local_config();
+        let content = serde_json::to_string(&build_catalog(&local, &[]).unwrap()).unwrap();
+        let parsed = || serde_json::from_str::<GameCatalog>(&content).unwrap().games;
+
+        // 导入回原配置：全部不变
+        let report = merge_into(local_config(), parsed(), false);
+        assert_eq!(report.unchanged, ["A", "B"]);
+        assert!(report.added.is_empty() && report.conflicts.is_empty());
+
+        // 导入到空配置：id 与定义原样保留
+        let empty: AppConfig =
+            serde_json::from_value(json!({ "version": 2, "games": [] })).unwrap();
+        let report = merge_into(empty, parsed(), false);
+        assert_eq!(report.added, ["A", "B"]);
+        for (imported, original) in report.config.games.iter().zip(&local.games) {
+            assert_eq!(imported.id, original.id);
+            assert_eq!(imported.name, original.name);
+            assert!(same_definition(
+                imported,
+                &GameDraft {
+                    name: original.name.clone(),
+                    path: original.path.clone(),
+                    icon: original.icon.clone(),
+                    kind: original.kind.clone(),
+                    steam_app_id: original.steam_app_id,
+                }
+            ));
+        }
+
+        let selected = build_catalog(&local, &["gb".to_string()]).unwrap();
+        assert_eq!(selected.games.len(), 1);
+        assert!(build_catalog(&local, &["missing".to_string()]).is_err());
+    }
+}
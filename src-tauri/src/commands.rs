@@ -1,12 +1,34 @@
 use crate::backup;
-use crate::config::{read_config, reorder_games as reorder_games_impl, update_setting};
-use crate::paths::{get_appdata_root, get_steam_install_dir_internal, get_user_home, list_steam_uid, resolve_template_path};
+use crate::catalog;
+use crate::ludusavi;
+use crate::config::{self, read_config, reorder_games as reorder_games_impl, update_setting, GameDraft};
+use crate::paths::{
+    get_appdata_root, get_steam_install_dir_internal, get_user_home, list_steam_uid, list_steam_users,
+    resolve_template_path, SteamUser,
+};
+use crate::retention::{self, RetentionPolicy};
+use crate::workdir;
+use std::path::Path;
 use tauri::command;
 
-/// 读取配置
+/// 读取配置；刚从配置 v1 升级时顺带把旧备份归位到各游戏的备份目录（只执行一次，结果通过 take_legacy_adoption 取走）
 #[command]
 pub fn load_config() -> Result<crate::config::AppConfig, String> {
-    read_config()
+    let config = read_config()?;
+    backup::adopt_pending_legacy_backups(&config);
+    Ok(config)
+}
+
+/// 取走最近一次配置自动恢复的记录（config.json 损坏时），没有则返回 None
+#[command]
+pub fn take_config_recovery() -> Option<crate::config::ConfigRecovery> {
+    crate::config::take_config_recovery()
+}
+
+/// 取走旧备份归位的结果（从配置 v1 升级后的首次启动），没有则返回 None
+#[command]
+pub fn take_legacy_adoption() -> Option<backup::LegacyAdoption> {
+    backup::take_legacy_adoption()
 }
 
 /// 获取用户主目录（取环境变量 USERPROFILE）
@@ -15,7 +37,7 @@ pub fn get_user_folder() -> Result<String, String> {
     get_user_home()
 }
 
-/// 获取 Steam 安装目录（注册表 Software\Valve\Steam ，没有则返回 None）
+/// 获取 Steam 安装目录（Windows 读注册表 Software\Valve\Steam，其他平台查找常见安装位置；没有则返回 None）
 #[command]
 pub fn get_steam_install_dir() -> Result<Option<String>, String> {
     match get_steam_install_dir_internal() {
@@ -30,14 +52,24 @@ pub fn get_steam_uid_list() -> Result<Vec<String>, String> {
     Ok(list_steam_uid())
 }
 
+/// 获取本机的 Steam 账号（含昵称与最近登录标记，最近登录的排在最前；失败返回空数组）
+#[command]
+pub fn get_steam_users() -> Result<Vec<SteamUser>, String> {
+    Ok(list_steam_users())
+}
+
 /// 检查存档路径是否存在：支持占位符替换
 #[command]
-pub fn check_save_path(path: String, steam_uid: Option<String>) -> Result<bool, String> {
+pub fn check_save_path(
+    path: String,
+    steam_uid: Option<String>,
+    app_id: Option<u32>,
+) -> Result<bool, String> {
     if path.contains("{SteamUID}") && steam_uid.is_none() {
         return Ok(false);
     }
 
-    let resolved = match resolve_template_path(path, steam_uid) {
+    let resolved = match resolve_template_path(path, steam_uid, app_id) {
         Ok(p) => p,
         Err(_) => return Ok(false),
     };
@@ -51,50 +83,131 @@ pub fn get_appdata_root_path() -> Result<String, String> {
     get_appdata_root()
 }
 
-/// 备份指定游戏存档：压缩存档目录为 Zip，备注写同名 .txt，更新配置中的 lastSave 字段
+/// 备份指定游戏存档：按设置压缩存档目录为 Zip/7z，备注写同名 .txt，更新配置中的 lastSave 字段
+/// 存档与最近一次备份相同时跳过（`force` 为 true 时强制备份）
+/// `all_steam_uids` 为 true 时把所有存在存档的 Steam 账号备份进同一个文件
 #[command]
 pub fn backup_game(
-    game_name: String,
+    game_id: String,
     path_template: String,
     steam_uid: Option<String>,
     remark: Option<String>,
+    force: Option<bool>,
+    all_steam_uids: Option<bool>,
 ) -> Result<backup::BackupResponse, String> {
-    backup::perform_backup(game_name, path_template, steam_uid, remark)
+    backup::perform_backup(
+        game_id,
+        path_template,
+        steam_uid,
+        remark,
+        force.unwrap_or(false),
+        all_steam_uids.unwrap_or(false),
+    )
+}
+
+/// 列出存档路径存在的 Steam 账号（路径中含 `{SteamUID}` 时用于多账号备份）
+#[command]
+pub fn list_save_accounts(path: String, app_id: Option<u32>) -> Result<Vec<String>, String> {
+    if !path.contains("{SteamUID}") {
+        return Ok(Vec::new());
+    }
+    Ok(backup::list_account_saves(&path, app_id)?
+        .into_iter()
+        .map(|(uid, _)| uid)
+        .collect())
 }
 
 /// 列出指定游戏的备份（自动读取备注与时间信息）
 #[command]
-pub fn list_backups(game_name: String) -> Result<Vec<backup::BackupEntry>, String> {
-    backup::list_backups(game_name)
+pub fn list_backups(game_id: String) -> Result<Vec<backup::BackupEntry>, String> {
+    backup::list_backups(game_id)
+}
+
+/// 完整校验指定备份（CRC 与清单哈希）
+#[command]
+pub fn verify_backup(game_id: String, file_name: String) -> Result<backup::VerifyReport, String> {
+    backup::verify_backup(game_id, file_name)
 }
 
-/// 复原指定备份：可配置是否在复原前额外备份，删除原存档后解压
+/// 按保留策略清理备份；`dry_run` 为 true 时只列出将被清理的备份
+#[command]
+pub fn prune_backups(game_id: String, dry_run: Option<bool>) -> Result<Vec<retention::PruneCandidate>, String> {
+    retention::prune_backups(&game_id, dry_run.unwrap_or(false))
+}
+
+/// 清理去重存储中不再被任何快照引用的内容，返回删除的数量（清理后回收站里的快照无法再复原）
+#[command]
+pub fn collect_backup_garbage(game_id: String) -> Result<usize, String> {
+    backup::collect_backup_garbage(&game_id)
+}
+
+/// 设置单个游戏的备份保留策略；`retention` 为空时恢复使用全局策略
+#[command]
+pub fn set_game_retention(
+    game_id: String,
+    retention: Option<RetentionPolicy>,
+) -> Result<crate::config::AppConfig, String> {
+    config::set_game_retention(&game_id, retention)
+}
+
+/// 复原指定备份：可配置是否在复原前额外备份，先解压到临时目录校验后再替换原存档
 #[command]
 pub fn restore_backup(
-    game_name: String,
+    game_id: String,
     path_template: String,
     backup_path: String,
     steam_uid: Option<String>,
 ) -> Result<backup::RestoreResponse, String> {
-    backup::restore_backup(game_name, path_template, backup_path, steam_uid)
+    backup::restore_backup(game_id, path_template, backup_path, steam_uid)
 }
 
 /// 更新备份备注（空字符串会删除备注文件）
 #[command]
-pub fn update_backup_remark(game_name: String, file_name: String, remark: String) -> Result<(), String> {
-    backup::update_backup_remark(game_name, file_name, remark)
+pub fn update_backup_remark(game_id: String, file_name: String, remark: String) -> Result<(), String> {
+    backup::update_backup_remark(game_id, file_name, remark)
+}
+
+/// 锁定或解除锁定备份（锁定的备份不会被自动清理）
+#[command]
+pub fn set_backup_pinned(game_id: String, file_name: String, pinned: bool) -> Result<(), String> {
+    backup::set_backup_pinned(game_id, file_name, pinned)
 }
 
-/// 删除备份文件及备注（送回收站）
+/// 删除备份文件及备注（送回收站）；已锁定的备份需要 `force` 为 true
 #[command]
-pub fn delete_backup(game_name: String, file_name: String) -> Result<(), String> {
-    backup::delete_backup(game_name, file_name)
+pub fn delete_backup(game_id: String, file_name: String, force: Option<bool>) -> Result<(), String> {
+    backup::delete_backup(game_id, file_name, force.unwrap_or(false))
+}
+
+/// 返回备份目录路径：指定游戏时返回该游戏的备份目录，否则返回备份根目录
+#[command]
+pub fn get_backup_dir(game_id: Option<String>) -> Result<String, String> {
+    match game_id {
+        Some(id) => backup::game_backup_dir(&id),
+        None => backup::backup_dir(),
+    }
+    .map(|p| p.to_string_lossy().to_string())
 }
 
-/// 返回备份目录路径
+/// 修改备份位置（`game_id` 为空时修改全局备份根目录，`location` 为空时恢复默认），并移动已有备份
 #[command]
-pub fn get_backup_dir() -> Result<String, String> {
-    backup::backup_dir().map(|p| p.to_string_lossy().to_string())
+pub fn set_backup_location(
+    game_id: Option<String>,
+    location: Option<String>,
+) -> Result<crate::config::AppConfig, String> {
+    backup::set_backup_location(game_id.as_deref(), location)
+}
+
+/// 返回工作目录路径及其来源（环境变量 / 便携模式 / 自定义 / 程序目录 / 用户数据目录）
+#[command]
+pub fn get_workdir_info() -> Result<workdir::WorkdirInfo, String> {
+    workdir::workdir_info()
+}
+
+/// 把整个工作目录（配置与全部备份）移动到新位置
+#[command]
+pub fn move_workdir(target: String) -> Result<workdir::WorkdirMove, String> {
+    workdir::move_workdir(Path::new(&target))
 }
 
 /// 更新 settings 中的单个键值
@@ -103,8 +216,63 @@ pub fn set_setting(key: String, value: serde_json::Value) -> Result<crate::confi
     update_setting(key, value)
 }
 
-/// 重排游戏顺序（写入 config.json）
+/// 按游戏 id 重排游戏顺序（写入 config.json）
 #[command]
 pub fn reorder_games(order: Vec<String>) -> Result<crate::config::AppConfig, String> {
     reorder_games_impl(order)
 }
+
+/// 新增游戏（校验名称唯一、占位符与类型）
+#[command]
+pub fn add_game(game: GameDraft) -> Result<crate::config::AppConfig, String> {
+    config::add_game(game)
+}
+
+/// 编辑游戏的名称、路径、图标与类型；名称变化时同时给已有备份改名
+#[command]
+pub fn update_game(game_id: String, game: GameDraft) -> Result<crate::config::AppConfig, String> {
+    let current_name = config::check_game_draft(&game_id, &game)?;
+    if game.name.trim() != current_name {
+        backup::rename_game(&game_id, &game.name)?;
+    }
+    config::update_game(&game_id, game)
+}
+
+/// 重命名游戏，备份与备注的文件名一起改为新名称（失败时全部回滚）
+#[command]
+pub fn rename_game(game_id: String, new_name: String) -> Result<crate::config::AppConfig, String> {
+    backup::rename_game(&game_id, &new_name)
+}
+
+/// 移除游戏；`delete_backups` 为 true 时同时将该游戏的全部备份与备注送回收站
+#[command]
+pub fn remove_game(game_id: String, delete_backups: Option<bool>) -> Result<crate::config::AppConfig, String> {
+    if delete_backups.unwrap_or(false) {
+        backup::trash_game_backups(&game_id)?;
+    }
+    config::remove_game(&game_id)
+}
+
+/// 导出游戏定义到 JSON 文件（`game_ids` 为空时导出全部），返回导出的数量
+#[command]
+pub fn export_games(game_ids: Vec<String>, file: String) -> Result<usize, String> {
+    catalog::export_games(&game_ids, Path::new(&file))
+}
+
+/// 从 JSON 文件导入游戏定义并与现有配置合并；`overwrite` 为 true 时用清单内容覆盖同 id 的游戏
+#[command]
+pub fn import_games(file: String, overwrite: Option<bool>) -> Result<catalog::ImportReport, String> {
+    catalog::import_games(Path::new(&file), overwrite.unwrap_or(false))
+}
+
+/// 在本地的 Ludusavi 清单（manifest.yaml）中按名称搜索游戏，返回转换好的路径模板
+#[command]
+pub fn search_ludusavi(file: String, query: String) -> Result<Vec<GameDraft>, String> {
+    ludusavi::search_manifest(Path::new(&file), &query)
+}
+
+/// 把 Ludusavi 清单中选中的游戏加入配置
+#[command]
+pub fn import_ludusavi(file: String, names: Vec<String>) -> Result<catalog::ImportReport, String> {
+    ludusavi::import_from_manifest(Path::new(&file), &names)
+}
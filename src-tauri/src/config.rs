@@ -1,15 +1,47 @@
+use crate::backup;
+use crate::migration;
+use crate::paths::validate_template;
+use crate::retention::RetentionPolicy;
+use crate::settings::{validate_setting, Settings};
+use crate::workdir::software_workdir;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::{env, fs, path::PathBuf};
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
 
-const WORK_DIR_NAME: &str = "game-sl";
 const CONFIG_FILE_NAME: &str = "config.json";
 // 编译时内置默认配置，初始化时写入软件目录
 const DEFAULT_CONFIG: &str = include_str!("default-config.json");
+/// 当前配置版本：v2 起每个游戏有固定的 id
+pub const CONFIG_VERSION: u32 = 2;
+/// 配置备份目录（工作目录下），保存最近几次写入前的 config.json
+const CONFIG_BACKUP_DIR: &str = "config-backup";
+/// 滚动保留的配置备份份数
+const CONFIG_BACKUP_COUNT: usize = 5;
+
+/// 读取配置时发生的自动恢复：config.json 损坏后改用备份（或默认配置）
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigRecovery {
+    /// 原配置读取失败的原因
+    pub error: String,
+    /// 损坏的配置改名保留后的路径
+    pub corrupt_path: Option<String>,
+    /// 用来恢复的备份路径；为 `None` 表示没有可用备份，已重置为默认配置
+    pub restored_from: Option<String>,
+}
+
+/// 最近一次自动恢复的记录，前端启动时取走并提示用户
+static LAST_RECOVERY: Mutex<Option<ConfigRecovery>> = Mutex::new(None);
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AppConfig {
-    pub settings: serde_json::Value,
+    #[serde(default)]
+    pub settings: Settings,
     pub games: Vec<GameEntry>,
     pub version: u32,
 }
@@ -17,6 +49,9 @@ pub struct AppConfig {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct GameEntry {
+    /// 固定的游戏 id：查找游戏与归属备份都用它，改名不受影响（v1 配置迁移时自动生成）
+    #[serde(default)]
+    pub id: String,
     pub name: String,
     pub path: String,
     pub icon: String,
@@ -24,42 +59,101 @@ pub struct GameEntry {
     pub last_save: Option<i64>,
     #[serde(rename = "type")]
     pub kind: Option<String>,
+    /// Steam App ID，在 Linux 上用于定位 Proton 前缀
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub steam_app_id: Option<u32>,
+    /// 单独的备份保留策略（为空时使用 settings.retention）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retention: Option<RetentionPolicy>,
+    /// 单独的备份目录，实际备份放在其中的 `<id>` 下（为空时使用备份根目录下的 `<id>`），通过 `set_backup_location` 修改
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backup_dir: Option<String>,
 }
 
-/// 确保 settings 中存在默认值；返回是否有改动（需要写回文件）
-fn ensure_settings_defaults(config: &mut AppConfig) -> bool {
-    let mut changed = false;
+/// 新增/编辑游戏时前端提交的可编辑字段
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GameDraft {
+    pub name: String,
+    pub path: String,
+    #[serde(default)]
+    pub icon: String,
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub steam_app_id: Option<u32>,
+}
 
-    if !config.settings.is_object() {
-        config.settings = serde_json::json!({});
-        changed = true;
-    }
+/// 游戏 `type` 字段允许的取值
+const GAME_KINDS: &[&str] = &["steam", "userdata"];
 
-    if let Some(map) = config.settings.as_object_mut() {
-        if !map.contains_key("restoreExtraBackup") {
-            map.insert("restoreExtraBackup".to_string(), serde_json::json!(true));
-            changed = true;
-        }
+/// 按 id 查找游戏
+pub fn find_game<'a>(config: &'a AppConfig, game_id: &str) -> Result<&'a GameEntry, String> {
+    config
+        .games
+        .iter()
+        .find(|g| g.id == game_id)
+        .ok_or_else(|| "未找到对应的游戏配置".to_string())
+}
+
+fn find_game_mut<'a>(config: &'a mut AppConfig, game_id: &str) -> Result<&'a mut GameEntry, String> {
+    config
+        .games
+        .iter_mut()
+        .find(|g| g.id == game_id)
+        .ok_or_else(|| "未找到对应的游戏配置".to_string())
+}
+
+/// 生成新的游戏 id：毫秒时间戳的十六进制，与已有 id 冲突（`taken` 返回 true）时追加序号
+pub fn new_game_id(taken: impl Fn(&str) -> bool) -> String {
+    let base = format!("g{:x}", chrono::Local::now().timestamp_millis());
+    let mut id = base.clone();
+    let mut n = 1;
+    while taken(&id) {
+        id = format!("{base}-{n}");
+        n += 1;
+    }
+    id
+}
 
-        // 默认启用相对时间，避免旧配置缺省时前端行为不一致
-        if !map.contains_key("useRelativeTime") {
-            map.insert("useRelativeTime".to_string(), serde_json::json!(true));
+/// 为缺少 id 的游戏补上 id（手动编辑或旧版本配置）；返回是否有改动
+fn ensure_game_ids(config: &mut AppConfig) -> bool {
+    let mut changed = false;
+    for i in 0..config.games.len() {
+        if config.games[i].id.is_empty() {
+            config.games[i].id = new_game_id(|id| config.games.iter().any(|g| g.id == id));
             changed = true;
         }
     }
-
     changed
 }
 
-/// 获取当前可读写的工作目录（软件同级目录下的 game-sl）
-pub fn software_workdir() -> Result<PathBuf, String> {
-    let exe_path = env::current_exe().map_err(|e| format!("无法获取程序路径: {e}"))?;
-    let base_dir = exe_path
-        .parent()
-        .ok_or_else(|| "无法获取程序所在目录".to_string())?;
-    let workdir = base_dir.join(WORK_DIR_NAME);
-    fs::create_dir_all(&workdir).map_err(|e| format!("创建工作目录失败: {e}"))?;
-    Ok(workdir)
+/// 检查游戏名称：不能为空，且不能与其他游戏重名
+fn validate_game_name(config: &AppConfig, name: &str, original_id: Option<&str>) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("游戏名称不能为空".to_string());
+    }
+    if config
+        .games
+        .iter()
+        .any(|g| g.name == name && Some(g.id.as_str()) != original_id)
+    {
+        return Err(format!("已存在同名游戏: {name}"));
+    }
+    Ok(())
+}
+
+/// 校验游戏字段；`original_id` 为被编辑游戏的 id（新增时为 `None`），用于排除自身的重名检查
+pub fn validate_game(config: &AppConfig, draft: &GameDraft, original_id: Option<&str>) -> Result<(), String> {
+    validate_game_name(config, draft.name.trim(), original_id)?;
+    validate_template(&draft.path)?;
+
+    if let Some(kind) = &draft.kind {
+        if !GAME_KINDS.contains(&kind.as_str()) {
+            return Err(format!("未知的游戏类型: {kind}"));
+        }
+    }
+    Ok(())
 }
 
 /// 确保 config.json 存在，不存在则根据内置模板生成
@@ -73,83 +167,233 @@ fn ensure_config_file() -> Result<PathBuf, String> {
     Ok(config_path)
 }
 
+/// 第 n 份配置备份的路径（1 为最新）
+fn config_backup_path(config_path: &Path, n: usize) -> PathBuf {
+    config_path
+        .with_file_name(CONFIG_BACKUP_DIR)
+        .join(format!("config.{n}.json"))
+}
+
+/// 先写临时文件并落盘，再改名覆盖目标，写到一半崩溃也不会留下半截的配置
+fn write_atomic(path: &Path, content: &str) -> Result<(), String> {
+    let tmp_path = path.with_extension("json.tmp");
+    let result = File::create(&tmp_path)
+        .and_then(|mut f| {
+            f.write_all(content.as_bytes())?;
+            f.sync_all()
+        })
+        .and_then(|_| fs::rename(&tmp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result.map_err(|e| format!("写入配置失败: {e}"))
+}
+
+/// 覆盖配置前把当前文件滚动保存到备份目录：config.1.json 最新，超出份数的最旧一份被覆盖
+/// 内容与最新一份备份相同时跳过，避免连续的小改动把较早的备份挤掉
+fn rotate_config_backups(config_path: &Path) -> Result<(), String> {
+    let Ok(current) = fs::read(config_path) else {
+        return Ok(());
+    };
+    let latest = config_backup_path(config_path, 1);
+    if fs::read(&latest).is_ok_and(|b| b == current) {
+        return Ok(());
+    }
+
+    if let Some(dir) = latest.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("创建配置备份目录失败: {e}"))?;
+    }
+    for n in (1..CONFIG_BACKUP_COUNT).rev() {
+        let from = config_backup_path(config_path, n);
+        if from.exists() {
+            fs::rename(&from, config_backup_path(config_path, n + 1))
+                .map_err(|e| format!("轮换配置备份失败: {e}"))?;
+        }
+    }
+    fs::write(&latest, current).map_err(|e| format!("写入配置备份失败: {e}"))
+}
+
+/// config.json 读不出来或不是合法 JSON 时的恢复：损坏的文件改名保留，按从新到旧的顺序找第一份可用的备份；
+/// 都不可用时退回内置默认配置。恢复结果写回 config.json，并记录下来供前端提示
+fn recover_config(config_path: &Path, error: String) -> Result<Value, String> {
+    let ts_tag = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let corrupt = config_path.with_file_name(format!("config.corrupt-{ts_tag}.json"));
+    let corrupt_path = fs::rename(config_path, &corrupt)
+        .ok()
+        .map(|_| corrupt.to_string_lossy().to_string());
+
+    let restored = (1..=CONFIG_BACKUP_COUNT).find_map(|n| {
+        let path = config_backup_path(config_path, n);
+        let raw: Value = serde_json::from_str(&fs::read_to_string(&path).ok()?).ok()?;
+        Some((path, raw))
+    });
+    let (restored_from, raw) = match restored {
+        Some((path, raw)) => (Some(path.to_string_lossy().to_string()), raw),
+        None => (
+            None,
+            serde_json::from_str(DEFAULT_CONFIG).map_err(|e| format!("解析默认配置失败: {e}"))?,
+        ),
+    };
+
+    let content =
+        serde_json::to_string_pretty(&raw).map_err(|e| format!("序列化配置失败: {e}"))?;
+    write_atomic(config_path, &content)?;
+
+    if let Ok(mut last) = LAST_RECOVERY.lock() {
+        *last = Some(ConfigRecovery {
+            error,
+            corrupt_path,
+            restored_from,
+        });
+    }
+    Ok(raw)
+}
+
+/// 取走最近一次自动恢复的记录（只会返回一次）
+pub fn take_config_recovery() -> Option<ConfigRecovery> {
+    LAST_RECOVERY.lock().ok()?.take()
+}
+
 /// 读取配置，必要时创建默认文件（如不存在则初始化默认配置）
+/// 配置损坏时自动从备份恢复，而不是让程序一直打不开
 pub fn read_config() -> Result<AppConfig, String> {
-    let config_path = ensure_config_file()?;
-    let content = fs::read_to_string(&config_path)
-        .map_err(|e| format!("读取配置失败: {e}"))?;
+    read_config_at(&ensure_config_file()?)
+}
+
+fn read_config_at(config_path: &Path) -> Result<AppConfig, String> {
+    let parsed = fs::read_to_string(config_path)
+        .map_err(|e| format!("读取配置失败: {e}"))
+        .and_then(|content| {
+            serde_json::from_str::<Value>(&content).map_err(|e| format!("解析配置失败: {e}"))
+        });
+    let mut raw = match parsed {
+        Ok(raw) => raw,
+        Err(e) => recover_config(config_path, e)?,
+    };
+
+    // 旧版本配置逐步升级到当前版本，升级前先留一份原文件
+    let version = migration::config_version(&raw);
+    let migrated = version != CONFIG_VERSION;
+    if migrated {
+        if version < CONFIG_VERSION {
+            migration::backup_before_migration(config_path, version)?;
+        }
+        // v1 的备份按游戏名放在备份根目录，等带 id 的配置落盘后由 `load_config` 归位
+        if version < 2 {
+            backup::mark_legacy_backups_pending(config_path)?;
+        }
+        migration::migrate(&mut raw, version)?;
+    }
+
+    let raw_settings = raw.get("settings").cloned();
     let mut config: AppConfig =
-        serde_json::from_str(&content).map_err(|e| format!("解析配置失败: {e}"))?;
+        serde_json::from_value(raw).map_err(|e| format!("解析配置失败: {e}"))?;
 
-    // 自动补全缺省字段，保持旧配置向下兼容
-    let changed = ensure_settings_defaults(&mut config);
+    // 自动补全缺省字段（缺少的设置项、游戏 id），保持旧配置向下兼容
+    let mut changed = ensure_game_ids(&mut config);
+    changed |= config.settings.to_file_value().ok() != raw_settings;
 
-    if changed {
-        write_config(&config)?;
+    if migrated {
+        // 升级前的原文件已另存为 `config.v1-*.json`，不放进配置备份：从备份恢复时会再迁移一次，
+        // 重新分配的 id 与已经归位的备份对不上。改为把升级后的配置作为最新一份备份
+        write_atomic(config_path, &config_file_content(&config)?)?;
+        rotate_config_backups(config_path)?;
+    } else if changed {
+        write_config_at(config_path, &config)?;
     }
 
     Ok(config)
 }
 
-/// 写回配置文件，保留格式化
+/// 写入 config.json 的结构：settings 中带上读取时无效的原值（返回给前端的 `AppConfig` 不带这些值）
+#[derive(Serialize)]
+struct ConfigFile<'a> {
+    settings: Value,
+    games: &'a [GameEntry],
+    version: u32,
+}
+
+fn config_file_content(config: &AppConfig) -> Result<String, String> {
+    let file = ConfigFile {
+        settings: config.settings.to_file_value()?,
+        games: &config.games,
+        version: config.version,
+    };
+    serde_json::to_string_pretty(&file).map_err(|e| format!("序列化配置失败: {e}"))
+}
+
+/// 写回配置文件，保留格式化；写入前滚动备份当前文件，写入本身是原子的
 pub fn write_config(config: &AppConfig) -> Result<(), String> {
-    let config_path = ensure_config_file()?;
-    let content = serde_json::to_string_pretty(config)
-        .map_err(|e| format!("序列化配置失败: {e}"))?;
-    fs::write(&config_path, content)
-        .map_err(|e| format!("写入配置失败: {e}"))
+    write_config_at(&ensure_config_file()?, config)
+}
+
+fn write_config_at(config_path: &Path, config: &AppConfig) -> Result<(), String> {
+    let content = config_file_content(config)?;
+    rotate_config_backups(config_path)?;
+    write_atomic(config_path, &content)
 }
 
 /// 更新指定游戏的 last_save 并落盘，返回最新配置
-pub fn update_last_save(game_name: &str, timestamp: i64) -> Result<AppConfig, String> {
+pub fn update_last_save(game_id: &str, timestamp: i64) -> Result<AppConfig, String> {
     let mut config = read_config()?;
-    let Some(entry) = config.games.iter_mut().find(|g| g.name == game_name) else {
-        return Err("未找到对应的游戏配置".to_string());
-    };
-
+    let entry = find_game_mut(&mut config, game_id)?;
     entry.last_save = Some(timestamp);
     write_config(&config)?;
     Ok(config)
 }
 
-/// 更新 settings 中的单个键值并落盘，返回最新配置
-pub fn update_setting(key: String, value: Value) -> Result<AppConfig, String> {
+/// 设置单个游戏的备份保留策略（整体覆盖全局策略）；为空时恢复使用全局策略
+pub fn set_game_retention(
+    game_id: &str,
+    retention: Option<RetentionPolicy>,
+) -> Result<AppConfig, String> {
+    if let Some(policy) = &retention {
+        policy.validate()?;
+    }
     let mut config = read_config()?;
+    find_game_mut(&mut config, game_id)?.retention = retention;
+    write_config(&config)?;
+    Ok(config)
+}
 
-    // 确保 settings 是对象
-    if !config.settings.is_object() {
-        config.settings = serde_json::json!({});
-    }
+/// 更新 settings 中的单个键值并落盘，返回最新配置；已知设置项的值类型不对时拒绝
+pub fn update_setting(key: String, value: Value) -> Result<AppConfig, String> {
+    validate_setting(&key, &value)?;
+    let mut config = read_config()?;
 
-    if let Some(map) = config.settings.as_object_mut() {
+    // 从文件中的内容出发，其他设置项读取时无效的原值继续保留
+    let mut settings = config.settings.to_file_value()?;
+    if let Some(map) = settings.as_object_mut() {
         map.insert(key, value);
     }
+    config.settings =
+        serde_json::from_value(settings).map_err(|e| format!("解析设置失败: {e}"))?;
 
     write_config(&config)?;
     Ok(config)
 }
 
-/// 按名称顺序重排顺序并保存到 JSON，然后返回最新配置（省得前端调 load_config 再查一次）
+/// 按 id 顺序重排顺序并保存到 JSON，然后返回最新配置（省得前端调 load_config 再查一次）
 pub fn reorder_games(order: Vec<String>) -> Result<AppConfig, String> {
     let mut config = read_config()?;
     let original = config.games.clone();
 
     use std::collections::HashMap; // 感谢 AI
-    let mut by_name: HashMap<String, GameEntry> =
-        config.games.into_iter().map(|g| (g.name.clone(), g)).collect();
+    let mut by_id: HashMap<String, GameEntry> =
+        config.games.into_iter().map(|g| (g.id.clone(), g)).collect();
 
-    let mut new_games: Vec<GameEntry> = Vec::with_capacity(by_name.len());
+    let mut new_games: Vec<GameEntry> = Vec::with_capacity(by_id.len());
 
     // 先按传入的顺序推进去
-    for name in order {
-        if let Some(entry) = by_name.remove(&name) {
+    for id in order {
+        if let Some(entry) = by_id.remove(&id) {
             new_games.push(entry);
         }
     }
 
     // 再按原顺序补齐遗漏项，以防丢失配置
     for g in original {
-        if let Some(entry) = by_name.remove(&g.name) {
+        if let Some(entry) = by_id.remove(&g.id) {
             new_games.push(entry);
         }
     }
@@ -158,3 +402,166 @@ pub fn reorder_games(order: Vec<String>) -> Result<AppConfig, String> {
     write_config(&config)?;
     Ok(config)
 }
+
+/// 新增游戏（追加到列表末尾），返回最新配置
+pub fn add_game(draft: GameDraft) -> Result<AppConfig, String> {
+    let mut config = read_config()?;
+    validate_game(&config, &draft, None)?;
+
+    let id = new_game_id(|id| config.games.iter().any(|g| g.id == id));
+    config.games.push(GameEntry {
+        id,
+        name: draft.name.trim().to_string(),
+        path: draft.path,
+        icon: draft.icon,
+        last_save: None,
+        kind: draft.kind,
+        steam_app_id: draft.steam_app_id,
+        retention: None,
+        backup_dir: None,
+    });
+    write_config(&config)?;
+    Ok(config)
+}
+
+/// 只校验不写入：编辑游戏前先确认字段合法，避免给备份改完名后才发现其他字段有误；返回当前名称
+pub fn check_game_draft(game_id: &str, draft: &GameDraft) -> Result<String, String> {
+    let config = read_config()?;
+    let current_name = find_game(&config, game_id)?.name.clone();
+    validate_game(&config, draft, Some(game_id))?;
+    Ok(current_name)
+}
+
+/// 编辑游戏的名称、路径、图标、类型与 Steam App ID（lastSave 等其他字段保持不变），返回最新配置
+/// 名称变化时需先经 `backup::rename_game` 给备份改名，这里只改配置
+pub fn update_game(game_id: &str, draft: GameDraft) -> Result<AppConfig, String> {
+    let mut config = read_config()?;
+    validate_game(&config, &draft, Some(game_id))?;
+
+    let entry = find_game_mut(&mut config, game_id)?;
+    entry.name = draft.name.trim().to_string();
+    entry.path = draft.path;
+    entry.icon = draft.icon;
+    entry.kind = draft.kind;
+    entry.steam_app_id = draft.steam_app_id;
+    write_config(&config)?;
+    Ok(config)
+}
+
+/// 检查游戏能否改名：游戏存在，新名称非空且不与其他游戏重名；返回当前名称
+pub fn check_game_rename(game_id: &str, new_name: &str) -> Result<String, String> {
+    let config = read_config()?;
+    let old_name = find_game(&config, game_id)?.name.clone();
+    validate_game_name(&config, new_name, Some(game_id))?;
+    Ok(old_name)
+}
+
+/// 只修改配置中的游戏名，返回最新配置（备份文件的改名由 `backup::rename_game` 负责）
+pub fn rename_game_entry(game_id: &str, new_name: &str) -> Result<AppConfig, String> {
+    let mut config = read_config()?;
+    let entry = find_game_mut(&mut config, game_id)?;
+    entry.name = new_name.to_string();
+    write_config(&config)?;
+    Ok(config)
+}
+
+/// 从配置中移除游戏，返回最新配置
+pub fn remove_game(game_id: &str) -> Result<AppConfig, String> {
+    let mut config = read_config()?;
+    let before = config.games.len();
+    config.games.retain(|g| g.id != game_id);
+    if config.games.len() == before {
+        return Err("未找到对应的游戏配置".to_string());
+    }
+
+    write_config(&config)?;
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_config(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("game-sl-config-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join(CONFIG_FILE_NAME)
+    }
+
+    fn game_ids(config: &AppConfig) -> Vec<String> {
+        config.games.iter().map(|g| g.id.clone()).collect()
+    }
+
+    /// 升级后、下一次写入前配置就损坏：恢复出的是升级后的配置，游戏 id 不变
+    #[test]
+    fn recovery_right_after_migration_keeps_game_ids() {
+        let config_path = temp_config("migrated");
+        fs::write(
+            &config_path,
+            r#"{"settings":{},"version":1,"games":[
+                {"name":"A","path":"{Home}\\a","icon":"","type":"userdata"},
+                {"name":"B","path":"{Home}\\b","icon":"","type":"userdata"}]}"#,
+        )
+        .unwrap();
+
+        let migrated = read_config_at(&config_path).unwrap();
+        assert_eq!(migrated.version, CONFIG_VERSION);
+        let ids = game_ids(&migrated);
+        let latest = config_backup_path(&config_path, 1);
+        let backup: Value = serde_json::from_str(&fs::read_to_string(&latest).unwrap()).unwrap();
+        assert_eq!(migration::config_version(&backup), CONFIG_VERSION);
+        assert!(!config_backup_path(&config_path, 2).exists());
+
+        fs::write(&config_path, "{\"settings\": {").unwrap();
+        let recovered = read_config_at(&config_path).unwrap();
+        assert_eq!(game_ids(&recovered), ids);
+        let recovery = take_config_recovery().unwrap();
+        assert_eq!(recovery.restored_from, Some(latest.to_string_lossy().to_string()));
+
+        let _ = fs::remove_dir_all(config_path.parent().unwrap());
+    }
+
+    /// 手动改错的设置项按默认值使用，但补全其他字段、修改设置时都不会从文件中删掉
+    #[test]
+    fn invalid_settings_survive_writes() {
+        let config_path = temp_config("invalid-settings");
+        fs::write(
+            &config_path,
+            r#"{"settings":{"useRelativeTime":"yes","backupRoot":"backup"},"version":2,"games":[]}"#,
+        )
+        .unwrap();
+
+        let mut config = read_config_at(&config_path).unwrap();
+        assert!(config.settings.use_relative_time);
+        assert!(config.settings.backup_root.is_none());
+        config.settings.manifest_sidecar = true;
+        write_config_at(&config_path, &config).unwrap();
+
+        let on_disk: Value =
+            serde_json::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+        assert_eq!(on_disk["settings"]["useRelativeTime"], "yes");
+        assert_eq!(on_disk["settings"]["backupRoot"], "backup");
+        assert_eq!(on_disk["settings"]["manifestSidecar"], true);
+
+        let _ = fs::remove_dir_all(config_path.parent().unwrap());
+    }
+
+    /// 之后的写入照常把写入前的配置滚动进备份
+    #[test]
+    fn writes_rotate_previous_config() {
+        let config_path = temp_config("rotate");
+        fs::write(&config_path, r#"{"settings":{},"version":2,"games":[]}"#).unwrap();
+        let mut config = read_config_at(&config_path).unwrap();
+        let before = fs::read_to_string(&config_path).unwrap();
+
+        config.settings.use_relative_time = !config.settings.use_relative_time;
+        write_config_at(&config_path, &config).unwrap();
+        assert_eq!(
+            fs::read_to_string(config_backup_path(&config_path, 1)).unwrap(),
+            before
+        );
+
+        let _ = fs::remove_dir_all(config_path.parent().unwrap());
+    }
+}
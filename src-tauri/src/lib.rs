@@ -1,7 +1,16 @@
 mod backup;
+mod catalog;
 mod commands;
 pub mod config;
+mod ludusavi;
+mod manifest;
+mod migration;
 mod paths;
+mod retention;
+mod settings;
+mod store;
+mod vdf;
+mod workdir;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -9,20 +18,40 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .invoke_handler(tauri::generate_handler![
             commands::load_config,
+            commands::take_config_recovery,
+            commands::take_legacy_adoption,
             commands::get_user_folder,
             commands::get_steam_install_dir,
             commands::get_steam_uid_list,
+            commands::get_steam_users,
             commands::check_save_path,
             paths::resolve_template_path,
             commands::get_appdata_root_path,
             commands::backup_game,
+            commands::list_save_accounts,
             commands::list_backups,
+            commands::verify_backup,
+            commands::prune_backups,
+            commands::set_game_retention,
+            commands::collect_backup_garbage,
             commands::restore_backup,
             commands::update_backup_remark,
+            commands::set_backup_pinned,
             commands::delete_backup,
             commands::get_backup_dir,
+            commands::set_backup_location,
+            commands::get_workdir_info,
+            commands::move_workdir,
             commands::set_setting,
-            commands::reorder_games
+            commands::reorder_games,
+            commands::add_game,
+            commands::update_game,
+            commands::rename_game,
+            commands::remove_game,
+            commands::export_games,
+            commands::import_games,
+            commands::search_ludusavi,
+            commands::import_ludusavi
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
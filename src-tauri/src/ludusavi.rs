@@ -0,0 +1,343 @@
+use crate::catalog::{self, CatalogGame, ImportConflict, ImportReport};
+use crate::config::GameDraft;
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+/// 搜索时最多返回的结果数
+const SEARCH_LIMIT: usize = 50;
+
+type Manifest = BTreeMap<String, ManifestGame>;
+
+/// 最近一次解析的清单：清单有好几 MB，搜索时每输入一次都重新解析太慢
+/// 文件路径、大小或修改时间变化时重新读取
+struct CachedManifest {
+    file: PathBuf,
+    len: u64,
+    modified: Option<SystemTime>,
+    games: Arc<Manifest>,
+}
+
+static MANIFEST_CACHE: Mutex<Option<CachedManifest>> = Mutex::new(None);
+
+/// Ludusavi 清单中的一个游戏（只读取用得到的字段）
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct ManifestGame {
+    #[serde(default)]
+    files: BTreeMap<String, Option<FileRule>>,
+    #[serde(default)]
+    install_dir: BTreeMap<String, IgnoredAny>,
+    #[serde(default)]
+    steam: Option<SteamInfo>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct FileRule {
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    when: Vec<Constraint>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Constraint {
+    os: Option<String>,
+    store: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SteamInfo {
+    id: Option<u32>,
+}
+
+impl FileRule {
+    /// 规则在 Windows 上是否适用（没有限制，或限制的系统为 Windows、商店为 Steam）
+    fn applies(&self) -> bool {
+        self.when.is_empty()
+            || self.when.iter().any(|c| {
+                c.os.as_deref().is_none_or(|os| os == "windows")
+                    && c.store.as_deref().is_none_or(|store| store == "steam")
+            })
+    }
+}
+
+/// 读取本地的 Ludusavi 清单（manifest.yaml），文件没有变化时直接使用上次解析的结果
+fn read_manifest(file: &Path) -> Result<Arc<Manifest>, String> {
+    let meta = fs::metadata(file).map_err(|e| format!("读取 Ludusavi 清单失败: {e}"))?;
+    let (len, modified) = (meta.len(), meta.modified().ok());
+
+    let mut cache = MANIFEST_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(cached) = cache.as_ref() {
+        if cached.file == file && cached.len == len && cached.modified == modified {
+            return Ok(cached.games.clone());
+        }
+    }
+
+    let content = fs::read_to_string(file).map_err(|e| format!("读取 Ludusavi 清单失败: {e}"))?;
+    let games: Arc<Manifest> = Arc::new(
+        serde_yaml_ng::from_str(&content).map_err(|e| format!("解析 Ludusavi 清单失败: {e}"))?,
+    );
+    *cache = Some(CachedManifest {
+        file: file.to_path_buf(),
+        len,
+        modified,
+        games: games.clone(),
+    });
+    Ok(games)
+}
+
+/// 把 Ludusavi 的路径转换为本程序的路径模板，含有无法对应的占位符时返回 None
+/// - `<base>` 为游戏安装目录，对应 `{SteamApp:<appid>}`（实际安装在哪个 Steam 库都能找到）
+/// - `<root>` 为商店根目录，`<storeUserId>` 为 Steam 用户 id，只对 Steam 游戏有效
+/// - 通配符只允许出现在最后一段，此时备份它所在的目录
+fn convert_path(raw: &str, game: &ManifestGame) -> Option<String> {
+    let steam_id = game.steam.as_ref().and_then(|s| s.id);
+    let is_steam = steam_id.is_some();
+    let install_dir = game.install_dir.keys().next();
+
+    let mut segments: Vec<&str> = raw.split(['/', '\\']).filter(|s| !s.is_empty()).collect();
+    let has_glob = |s: &str| s.contains(['*', '?', '[']);
+    if segments.last().is_some_and(|s| has_glob(s)) {
+        segments.pop();
+    }
+    if segments.is_empty() || segments.iter().any(|s| has_glob(s)) {
+        return None;
+    }
+
+    let mut path = segments.join("\\");
+    let mut replace = |placeholder: &str, value: Option<String>| -> Option<()> {
+        if path.contains(placeholder) {
+            path = path.replace(placeholder, &value?);
+        }
+        Some(())
+    };
+    replace("<base>", steam_id.map(|id| format!("{{SteamApp:{id}}}")))?;
+    replace("<game>", install_dir.filter(|_| is_steam).cloned())?;
+    replace("<root>", is_steam.then(|| "{Steam}".to_string()))?;
+    replace("<storeUserId>", is_steam.then(|| "{SteamUID}".to_string()))?;
+    replace("<winAppData>", Some("{AppData}\\Roaming".to_string()))?;
+    replace("<winLocalAppData>", Some("{AppData}\\Local".to_string()))?;
+    replace("<winLocalAppDataLow>", Some("{AppData}\\LocalLow".to_string()))?;
+    replace("<winDocuments>", Some("{Home}\\Documents".to_string()))?;
+    replace("<home>", Some("{Home}".to_string()))?;
+
+    // 其余占位符（<osUserName>、<xdgData>、<winProgramData> 等）以及注册表暂不支持
+    if path.contains(['<', '>']) || !path.starts_with('{') {
+        return None;
+    }
+    Some(path)
+}
+
+/// 为游戏挑选一条可以转换的存档路径：优先带 `save` 标签的规则
+fn convert_game(name: &str, game: &ManifestGame) -> Option<GameDraft> {
+    let mut rules: Vec<(&String, bool)> = game
+        .files
+        .iter()
+        .filter(|(_, rule)| rule.as_ref().is_none_or(FileRule::applies))
+        .map(|(path, rule)| {
+            let is_save = rule
+                .as_ref()
+                .is_some_and(|r| r.tags.iter().any(|t| t == "save"));
+            (path, is_save)
+        })
+        .collect();
+    rules.sort_by_key(|(_, is_save)| !is_save);
+
+    let path = rules.iter().find_map(|(raw, _)| convert_path(raw, game))?;
+    let kind = if path.starts_with("{Steam") { "steam" } else { "userdata" };
+    Some(GameDraft {
+        name: name.to_string(),
+        path,
+        icon: String::new(),
+        kind: Some(kind.to_string()),
+        steam_app_id: game.steam.as_ref().and_then(|s| s.id),
+    })
+}
+
+/// 在 Ludusavi 清单中按名称搜索（不区分大小写），只返回存档路径能转换为路径模板的游戏
+pub fn search_manifest(file: &Path, query: &str) -> Result<Vec<GameDraft>, String> {
+    let query = query.trim().to_lowercase();
+    let manifest = read_manifest(file)?;
+    Ok(manifest
+        .iter()
+        .filter(|(name, _)| name.to_lowercase().contains(&query))
+        .filter_map(|(name, game)| convert_game(name, game))
+        .take(SEARCH_LIMIT)
+        .collect())
+}
+
+/// 把 Ludusavi 清单中指定名称的游戏加入配置；找不到或路径无法转换的记为冲突
+pub fn import_from_manifest(file: &Path, names: &[String]) -> Result<ImportReport, String> {
+    let manifest = read_manifest(file)?;
+    let mut games = Vec::new();
+    let mut skipped = Vec::new();
+    for name in names {
+        let reason = match manifest.get(name) {
+            None => "Ludusavi 清单中没有这个游戏",
+            Some(game) => match convert_game(name, game) {
+                Some(game) => {
+                    games.push(CatalogGame { id: None, game });
+                    continue;
+                }
+                None => "存档路径无法转换为路径模板",
+            },
+        };
+        skipped.push(ImportConflict {
+            name: name.clone(),
+            existing: None,
+            reason: reason.to_string(),
+        });
+    }
+
+    let mut report = catalog::merge_games(games, false)?;
+    report.conflicts.extend(skipped);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 与 Ludusavi 的 manifest.yaml 格式相同的小清单
+    const MANIFEST: &str = r#"
+Steamy:
+  files:
+    <root>/userdata/<storeUserId>/123/remote:
+      tags:
+        - save
+    <base>/cfg.ini:
+      tags:
+        - config
+  installDir:
+    Steamy: {}
+  steam:
+    id: 123
+Prefers Save:
+  files:
+    <winAppData>/Studio/settings.ini:
+      tags:
+        - config
+    <winDocuments>/My Games/Prefers Save/*.sav:
+      tags:
+        - save
+Platforms:
+  files:
+    <home>/.local/share/Platforms:
+      tags:
+        - save
+      when:
+        - os: linux
+    <winLocalAppData>/Platforms/GOG:
+      tags:
+        - save
+      when:
+        - store: gog
+    <winLocalAppData>/Platforms/Steam:
+      tags:
+        - save
+      when:
+        - os: windows
+          store: steam
+User Named:
+  files:
+    <home>/<osUserName>/saves:
+      tags:
+        - save
+Registry Only:
+  registry:
+    HKEY_CURRENT_USER/Software/Registry Only:
+      tags:
+        - save
+"#;
+
+    fn manifest() -> Manifest {
+        serde_yaml_ng::from_str(MANIFEST).unwrap()
+    }
+
+    fn converted(name: &str) -> Option<GameDraft> {
+        convert_game(name, &manifest()[name])
+    }
+
+    #[test]
+    fn steam_placeholders() {
+        let manifest = manifest();
+        let steamy = &manifest["Steamy"];
+        assert_eq!(
+            convert_path("<base>/Saves", steamy).as_deref(),
+            Some("{SteamApp:123}\\Saves")
+        );
+        assert_eq!(
+            convert_path("<root>/userdata/<storeUserId>/123/remote", steamy).as_deref(),
+            Some("{Steam}\\userdata\\{SteamUID}\\123\\remote")
+        );
+
+        // 没有 Steam App ID 的游戏无法定位安装目录与 Steam 用户目录
+        let other = &manifest["Prefers Save"];
+        assert_eq!(convert_path("<base>/Saves", other), None);
+        assert_eq!(convert_path("<root>/userdata/<storeUserId>", other), None);
+
+        let draft = converted("Steamy").unwrap();
+        assert_eq!(draft.path, "{Steam}\\userdata\\{SteamUID}\\123\\remote");
+        assert_eq!(draft.kind.as_deref(), Some("steam"));
+        assert_eq!(draft.steam_app_id, Some(123));
+    }
+
+    #[test]
+    fn windows_placeholders() {
+        let game = ManifestGame::default();
+        assert_eq!(
+            convert_path("<winAppData>/Studio", &game).as_deref(),
+            Some("{AppData}\\Roaming\\Studio")
+        );
+        assert_eq!(
+            convert_path("<winLocalAppDataLow>/Team Cherry", &game).as_deref(),
+            Some("{AppData}\\LocalLow\\Team Cherry")
+        );
+        assert_eq!(
+            convert_path("<winDocuments>/My Games", &game).as_deref(),
+            Some("{Home}\\Documents\\My Games")
+        );
+    }
+
+    #[test]
+    fn globs() {
+        let game = ManifestGame::default();
+        assert_eq!(
+            convert_path("<winAppData>/Studio/*.sav", &game).as_deref(),
+            Some("{AppData}\\Roaming\\Studio")
+        );
+        assert_eq!(convert_path("<winAppData>/*/save.dat", &game), None);
+        assert_eq!(convert_path("<winAppData>/Slot[12]/save.dat", &game), None);
+    }
+
+    #[test]
+    fn unsupported_placeholders_and_registry() {
+        let game = ManifestGame::default();
+        assert_eq!(convert_path("<home>/<osUserName>/saves", &game), None);
+        assert_eq!(convert_path("<xdgData>/Game", &game), None);
+        assert_eq!(convert_path("C:/Games/Save", &game), None);
+        assert!(converted("User Named").is_none());
+        assert!(converted("Registry Only").is_none());
+    }
+
+    #[test]
+    fn prefers_save_rules() {
+        let draft = converted("Prefers Save").unwrap();
+        assert_eq!(draft.path, "{Home}\\Documents\\My Games\\Prefers Save");
+        assert_eq!(draft.kind.as_deref(), Some("userdata"));
+        assert_eq!(draft.steam_app_id, None);
+    }
+
+    #[test]
+    fn rules_filtered_by_os_and_store() {
+        let draft = converted("Platforms").unwrap();
+        assert_eq!(draft.path, "{AppData}\\Local\\Platforms\\Steam");
+    }
+}
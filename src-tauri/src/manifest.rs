@@ -0,0 +1,265 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{copy, Read};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// 内嵌在压缩包根目录的清单文件名（复原时不会解压到存档目录）
+pub const MANIFEST_ENTRY: &str = ".game-sl-manifest.json";
+/// 当前清单格式版本（2：新增 `accounts`，记录多账号备份中各 Steam 账号的存档目录；
+/// 3：新增 `dirs`，记录去重快照中的目录，复原时重建空目录）
+pub const MANIFEST_VERSION: u32 = 3;
+
+/// 备份清单：记录备份来源与每个文件的大小、修改时间和 SHA-256
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupManifest {
+    pub manifest_version: u32,
+    pub game_name: String,
+    /// 备份时解析出的实际存档路径（多账号备份时为路径模板）
+    pub source_path: String,
+    pub steam_uid: Option<String>,
+    pub app_version: String,
+    pub config_version: u32,
+    pub created_at: i64,
+    pub files: Vec<ManifestFile>,
+    /// 多账号备份：每个账号的存档放在压缩包中以账号 id 命名的目录下；单账号备份为空
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub accounts: Vec<AccountSource>,
+    /// 去重快照中的目录（路径规则同 `files`）：快照不像压缩包那样自带目录条目，空目录靠它复原
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dirs: Vec<String>,
+}
+
+/// 多账号备份中的一个 Steam 账号
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountSource {
+    pub steam_uid: String,
+    /// 备份时该账号解析出的实际存档路径
+    pub source_path: String,
+}
+
+/// 清单中的单个文件（路径相对于存档目录，统一使用 `/` 分隔）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestFile {
+    pub path: String,
+    pub size: u64,
+    /// 最后修改时间（毫秒），读取失败时为 `None`
+    pub modified: Option<i64>,
+    pub sha256: String,
+}
+
+/// 列表展示用的清单摘要（不带文件明细）
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestSummary {
+    pub game_name: String,
+    pub source_path: String,
+    pub steam_uid: Option<String>,
+    pub app_version: String,
+    pub config_version: u32,
+    pub created_at: i64,
+    pub file_count: usize,
+    pub total_size: u64,
+    /// 多账号备份包含的账号 id
+    pub accounts: Vec<String>,
+}
+
+impl BackupManifest {
+    /// 创建空清单，文件明细在压缩过程中逐个补充
+    pub fn new(
+        game_name: &str,
+        source_path: &Path,
+        steam_uid: Option<String>,
+        config_version: u32,
+        created_at: i64,
+    ) -> Self {
+        BackupManifest {
+            manifest_version: MANIFEST_VERSION,
+            game_name: game_name.to_string(),
+            source_path: source_path.to_string_lossy().to_string(),
+            steam_uid,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            config_version,
+            created_at,
+            files: Vec::new(),
+            accounts: Vec::new(),
+            dirs: Vec::new(),
+        }
+    }
+
+    pub fn summary(&self) -> ManifestSummary {
+        ManifestSummary {
+            game_name: self.game_name.clone(),
+            source_path: self.source_path.clone(),
+            steam_uid: self.steam_uid.clone(),
+            app_version: self.app_version.clone(),
+            config_version: self.config_version,
+            created_at: self.created_at,
+            file_count: self.files.len(),
+            total_size: self.files.iter().map(|f| f.size).sum(),
+            accounts: self.accounts.iter().map(|a| a.steam_uid.clone()).collect(),
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("序列化备份清单失败: {e}"))
+    }
+
+    pub fn from_json(content: &str) -> Result<Self, String> {
+        serde_json::from_str(content).map_err(|e| format!("解析备份清单失败: {e}"))
+    }
+}
+
+/// 边读边计算 SHA-256 的读取器：压缩时顺带算出哈希，不需要再读一遍文件
+pub struct HashingReader<R: Read> {
+    inner: R,
+    hasher: Sha256,
+}
+
+impl<R: Read> HashingReader<R> {
+    pub fn new(inner: R) -> Self {
+        HashingReader {
+            inner,
+            hasher: Sha256::new(),
+        }
+    }
+
+    /// 返回十六进制小写的哈希值
+    pub fn finish(self) -> String {
+        to_hex(&self.hasher.finalize())
+    }
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// 参与备份的存档目录：`prefix` 为它在压缩包中所在的目录（单个存档目录时为空，多账号备份时为账号 id）
+pub struct ArchiveSource<'a> {
+    pub prefix: String,
+    pub dir: &'a Path,
+}
+
+impl<'a> ArchiveSource<'a> {
+    /// 存档目录直接放在压缩包根目录
+    pub fn root(dir: &'a Path) -> Self {
+        ArchiveSource {
+            prefix: String::new(),
+            dir,
+        }
+    }
+
+    /// 多账号备份：存档目录放在以账号 id 命名的目录下
+    pub fn account(steam_uid: &str, dir: &'a Path) -> Self {
+        ArchiveSource {
+            prefix: steam_uid.to_string(),
+            dir,
+        }
+    }
+}
+
+/// 待压缩的条目：实际路径与压缩包内的名称（统一使用 `/` 分隔）
+pub struct SourceEntry {
+    pub path: PathBuf,
+    pub name: String,
+    pub is_dir: bool,
+}
+
+/// 遍历所有存档目录，列出待压缩的文件与目录；带前缀的目录本身也作为一个目录条目，空存档也能原样复原
+pub fn source_entries(sources: &[ArchiveSource]) -> Result<Vec<SourceEntry>, String> {
+    let mut entries = Vec::new();
+    for source in sources {
+        for entry in WalkDir::new(source.dir) {
+            let entry = entry.map_err(|e| format!("遍历备份目录失败: {e}"))?;
+            let relative = entry
+                .path()
+                .strip_prefix(source.dir)
+                .map_err(|e| format!("路径处理失败: {e}"))?
+                .to_string_lossy()
+                .replace('\\', "/");
+            let name = match (source.prefix.is_empty(), relative.is_empty()) {
+                (true, true) => continue,
+                (true, false) => relative,
+                (false, true) => source.prefix.clone(),
+                (false, false) => format!("{}/{relative}", source.prefix),
+            };
+            let is_dir = entry.file_type().is_dir();
+            if !is_dir && !entry.file_type().is_file() {
+                continue;
+            }
+            entries.push(SourceEntry {
+                path: entry.into_path(),
+                name,
+                is_dir,
+            });
+        }
+    }
+    Ok(entries)
+}
+
+/// 判断存档目录的内容是否与清单中的文件列表一致（路径 + 大小 + SHA-256，不比较修改时间）
+/// 先比对路径与大小，全部一致时才逐个计算哈希，存档有改动时通常不需要读文件内容
+pub fn matches_sources(files: &[ManifestFile], sources: &[ArchiveSource]) -> Result<bool, String> {
+    let expected: HashMap<&str, &ManifestFile> =
+        files.iter().map(|f| (f.path.as_str(), f)).collect();
+
+    let mut actual = Vec::new();
+    for entry in source_entries(sources)? {
+        if entry.is_dir {
+            continue;
+        }
+        let size = fs::metadata(&entry.path)
+            .map_err(|e| format!("读取文件信息失败: {e}"))?
+            .len();
+
+        match expected.get(entry.name.as_str()) {
+            Some(f) if f.size == size => actual.push((entry.path, *f)),
+            _ => return Ok(false),
+        }
+    }
+
+    if actual.len() != expected.len() {
+        return Ok(false);
+    }
+
+    for (path, file) in actual {
+        let f = File::open(&path).map_err(|e| format!("读取文件失败: {e}"))?;
+        let mut reader = HashingReader::new(f);
+        copy(&mut reader, &mut std::io::sink()).map_err(|e| format!("读取文件内容失败: {e}"))?;
+        if !reader.finish().eq_ignore_ascii_case(&file.sha256) {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// 同名清单副本的路径：`xxx-Backup-20250101-120000.zip` -> `xxx-Backup-20250101-120000.manifest.json`
+pub fn sidecar_path(archive_path: &Path) -> PathBuf {
+    archive_path.with_extension("manifest.json")
+}
+
+/// 读取同名清单副本（不存在或解析失败时返回 `None`）
+pub fn read_sidecar(archive_path: &Path) -> Option<BackupManifest> {
+    let content = fs::read_to_string(sidecar_path(archive_path)).ok()?;
+    BackupManifest::from_json(&content).ok()
+}
+
+/// 写出同名清单副本
+pub fn write_sidecar(archive_path: &Path, manifest: &BackupManifest) -> Result<PathBuf, String> {
+    let path = sidecar_path(archive_path);
+    fs::write(&path, manifest.to_json()?).map_err(|e| format!("写入备份清单失败: {e}"))?;
+    Ok(path)
+}
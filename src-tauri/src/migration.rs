@@ -0,0 +1,177 @@
+use crate::config::{new_game_id, CONFIG_VERSION};
+use chrono::Local;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 单步迁移：把 `from` 版本的配置升级到 `from + 1`
+/// 迁移直接操作原始 JSON，旧版本的结构不一定能反序列化为当前的 `AppConfig`
+struct Migration {
+    from: u32,
+    apply: fn(&mut Value) -> Result<(), String>,
+}
+
+/// 按版本顺序排列的迁移步骤，新增配置版本时在末尾追加
+const MIGRATIONS: &[Migration] = &[Migration {
+    from: 1,
+    apply: migrate_v1_to_v2,
+}];
+
+/// 读取配置文件中的版本号；缺失时视为最早的 v1
+pub fn config_version(raw: &Value) -> u32 {
+    raw.get("version")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as u32)
+        .unwrap_or(1)
+}
+
+/// 把配置从 `from` 版本逐步升级到当前版本
+/// 高于当前版本的配置由更新的程序写出，直接拒绝，避免旧程序写回时丢失新字段
+pub fn migrate(raw: &mut Value, from: u32) -> Result<(), String> {
+    migrate_with(raw, from, MIGRATIONS)
+}
+
+/// 使用指定的迁移步骤升级，测试中用来模拟缺少步骤的情况
+fn migrate_with(raw: &mut Value, from: u32, migrations: &[Migration]) -> Result<(), String> {
+    if from > CONFIG_VERSION {
+        return Err(format!(
+            "配置文件版本为 {from}，高于当前程序支持的版本 {CONFIG_VERSION}，请升级程序后再打开"
+        ));
+    }
+
+    for version in from..CONFIG_VERSION {
+        let step = migrations
+            .iter()
+            .find(|m| m.from == version)
+            .ok_or_else(|| format!("缺少从版本 {version} 升级的迁移步骤"))?;
+        (step.apply)(raw).map_err(|e| format!("配置从版本 {version} 升级失败: {e}"))?;
+        raw["version"] = Value::from(version + 1);
+    }
+    Ok(())
+}
+
+/// 迁移前把原配置复制一份：`config.v1-20250101-120000.json`，迁移出问题时可以手动找回
+pub fn backup_before_migration(config_path: &Path, from: u32) -> Result<PathBuf, String> {
+    let ts_tag = Local::now().format("%Y%m%d-%H%M%S");
+    let stem = config_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("config");
+    let copy_path = config_path.with_file_name(format!("{stem}.v{from}-{ts_tag}.json"));
+    fs::copy(config_path, &copy_path).map_err(|e| format!("备份迁移前的配置失败: {e}"))?;
+    Ok(copy_path)
+}
+
+/// v1 -> v2：为每个游戏分配 id
+/// 旧备份要等带 id 的配置落盘之后才移动（见 `backup::adopt_pending_legacy_backups`），否则中途失败时
+/// 下次启动会分配新的 id，已经移走的备份就没有游戏对应了
+fn migrate_v1_to_v2(raw: &mut Value) -> Result<(), String> {
+    let Some(games) = raw.get_mut("games").and_then(|g| g.as_array_mut()) else {
+        return Ok(());
+    };
+
+    let mut ids: Vec<String> = games
+        .iter()
+        .filter_map(|g| g.get("id").and_then(|v| v.as_str()))
+        .filter(|id| !id.is_empty())
+        .map(|id| id.to_string())
+        .collect();
+    for game in games.iter_mut() {
+        let has_id = game
+            .get("id")
+            .and_then(|v| v.as_str())
+            .is_some_and(|id| !id.is_empty());
+        if has_id {
+            continue;
+        }
+        let id = new_game_id(|candidate| ids.iter().any(|i| i == candidate));
+        if let Some(obj) = game.as_object_mut() {
+            obj.insert("id".to_string(), Value::from(id.clone()));
+        }
+        ids.push(id);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn game_ids(raw: &Value) -> Vec<String> {
+        raw["games"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|g| g["id"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn missing_version_means_v1() {
+        assert_eq!(config_version(&json!({ "games": [] })), 1);
+        assert_eq!(config_version(&json!({ "version": 2 })), 2);
+    }
+
+    #[test]
+    fn v1_to_v2_keeps_existing_ids_and_never_duplicates() {
+        let mut raw = json!({
+            "games": [
+                { "name": "A" },
+                { "name": "B", "id": "keep-me" },
+                { "name": "C", "id": "" },
+                { "name": "D" },
+            ],
+        });
+        migrate(&mut raw, 1).unwrap();
+
+        assert_eq!(raw["version"], CONFIG_VERSION);
+        let ids = game_ids(&raw);
+        assert_eq!(ids[1], "keep-me");
+        assert!(ids.iter().all(|id| !id.is_empty()), "{ids:?}");
+        let mut unique = ids.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), ids.len(), "{ids:?}");
+    }
+
+    #[test]
+    fn v1_without_games_migrates() {
+        let mut raw = json!({ "settings": {} });
+        migrate(&mut raw, 1).unwrap();
+        assert_eq!(raw["version"], CONFIG_VERSION);
+    }
+
+    #[test]
+    fn newer_config_is_refused() {
+        let mut raw = json!({ "version": CONFIG_VERSION + 1, "games": [] });
+        let err = migrate(&mut raw, CONFIG_VERSION + 1).unwrap_err();
+        assert!(err.contains("高于当前程序支持的版本"), "{err}");
+        assert_eq!(raw["version"], CONFIG_VERSION + 1);
+    }
+
+    #[test]
+    fn missing_step_is_an_error() {
+        let mut raw = json!({ "games": [] });
+        let err = migrate_with(&mut raw, 1, &[]).unwrap_err();
+        assert!(err.contains("缺少从版本 1 升级的迁移步骤"), "{err}");
+    }
+
+    #[test]
+    fn backup_before_migration_copies_config() {
+        let dir = std::env::temp_dir().join(format!("game-sl-migration-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let config_path = dir.join("config.json");
+        fs::write(&config_path, r#"{"games":[]}"#).unwrap();
+
+        let copy = backup_before_migration(&config_path, 1).unwrap();
+        let name = copy.file_name().unwrap().to_string_lossy().to_string();
+        assert!(name.starts_with("config.v1-") && name.ends_with(".json"), "{name}");
+        assert_eq!(copy.parent(), Some(dir.as_path()));
+        assert_eq!(fs::read_to_string(&copy).unwrap(), r#"{"games":[]}"#);
+        assert!(config_path.is_file());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,18 +1,27 @@
+use crate::vdf;
+use serde::Serialize;
 use std::{env, fs, path::{Path, PathBuf}};
 use tauri::command;
 
 /// 统一路径分隔符（处理混用的 / 和 \ ）
-/// 仅在 Windows 下使用，将所有分隔符规范为反斜杠。
+/// 按当前平台规范：Windows 下统一为反斜杠，其他平台统一为正斜杠。
 fn normalize_path_separators<S: AsRef<str>>(s: S) -> String {
-    let mut out = s.as_ref().replace('/', "\\");
-    // 折叠连续的反斜杠（避免出现 \\\\ 这类情况）
-    while out.contains("\\\\") {
-        out = out.replace("\\\\", "\\");
+    normalize_separators_to(s.as_ref(), std::path::MAIN_SEPARATOR)
+}
+
+/// 把 / 与 \ 统一为 `sep`，并折叠连续的分隔符（避免出现 \\\\ 或 // 这类情况）
+fn normalize_separators_to(s: &str, sep: char) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        let c = if c == '/' || c == '\\' { sep } else { c };
+        if !(c == sep && out.ends_with(sep)) {
+            out.push(c);
+        }
     }
     out
 }
 
-/// 获取用户主目录 C:\\Users\\用户名\\
+/// 获取用户主目录 C:\\Users\\用户名\\（其他平台为 $HOME）
 pub fn get_user_home() -> Result<String, String> {
     if let Ok(path) = env::var("USERPROFILE") {
         return Ok(normalize_path_separators(path));
@@ -37,6 +46,18 @@ pub fn get_appdata_root() -> Result<String, String> {
     ))
 }
 
+/// 当前用户的应用数据目录：Windows 为 %LOCALAPPDATA%，其他平台为 $XDG_DATA_HOME 或 ~/.local/share
+/// 环境变量不是绝对路径时忽略（与 `get_xdg_dir` 一致），否则结果会随当前工作目录变化
+pub fn get_user_data_dir() -> Result<PathBuf, String> {
+    for var in ["LOCALAPPDATA", "XDG_DATA_HOME"] {
+        if let Some(dir) = env::var_os(var).filter(|v| Path::new(v).is_absolute()) {
+            return Ok(PathBuf::from(dir));
+        }
+    }
+    let home = env::var_os("HOME").ok_or_else(|| "无法获取用户目录".to_string())?;
+    Ok(PathBuf::from(home).join(".local").join("share"))
+}
+
 /// 读取注册表获取 Steam 安装目录（仅 Windows）
 #[cfg(target_os = "windows")]
 pub fn get_steam_install_dir_internal() -> Result<String, String> {
@@ -53,17 +74,244 @@ pub fn get_steam_install_dir_internal() -> Result<String, String> {
     Ok(normalize_path_separators(path))
 }
 
-// 如果我不编译到其他平台是不是就用不上这段？
+/// 其他平台按常见位置查找 Steam 安装目录
 #[cfg(not(target_os = "windows"))]
 pub fn get_steam_install_dir_internal() -> Result<String, String> {
-    Err("仅支持 Windows 平台".to_string())
+    let home = get_user_home()?;
+    find_steam_dir_in(Path::new(&home))
+        .map(|dir| dir.to_string_lossy().to_string())
+        .ok_or_else(|| "未找到 Steam 安装目录".to_string())
+}
+
+/// Steam 在 Linux（含 Steam Deck）与 macOS 上的常见安装位置（相对用户主目录）
+/// `~/.steam/steam` 通常是指向实际目录的符号链接；Flatpak 版安装在 `~/.var/app` 下
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+const STEAM_DIR_CANDIDATES: &[&str] = &[
+    ".steam/steam",
+    ".steam/root",
+    ".local/share/Steam",
+    ".var/app/com.valvesoftware.Steam/.local/share/Steam",
+    "Library/Application Support/Steam",
+];
+
+/// 在用户主目录下查找第一个包含 `steamapps` 的 Steam 目录（解析符号链接）
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+fn find_steam_dir_in(home: &Path) -> Option<PathBuf> {
+    STEAM_DIR_CANDIDATES
+        .iter()
+        .map(|candidate| home.join(candidate))
+        .filter(|dir| dir.join("steamapps").is_dir())
+        .find_map(|dir| dir.canonicalize().ok())
+}
+
+/// XDG 基础目录：环境变量为绝对路径时使用，否则为主目录下的默认位置
+fn get_xdg_dir(var: &str, fallback: &str) -> Result<String, String> {
+    if let Some(dir) = env::var_os(var).filter(|v| Path::new(v).is_absolute()) {
+        return Ok(normalize_path_separators(dir.to_string_lossy()));
+    }
+    let home = get_user_home()?;
+    Ok(normalize_path_separators(Path::new(&home).join(fallback).to_string_lossy()))
+}
+
+/// 非 Windows 平台上没有 AppData：把 `{AppData}\Roaming` 换成 `{XdgConfig}`，
+/// `{AppData}\Local`、`{AppData}\LocalLow` 换成 `{XdgData}`（原生移植版游戏大多按此存放，只是近似对应）
+fn map_appdata_to_xdg(template: &str) -> String {
+    let segments: Vec<&str> = template.split(['/', '\\']).collect();
+    let mut out: Vec<&str> = Vec::with_capacity(segments.len());
+    let mut i = 0;
+    while i < segments.len() {
+        let mapped = match (segments[i], segments.get(i + 1)) {
+            ("{AppData}", Some(&"Roaming")) => Some("{XdgConfig}"),
+            ("{AppData}", Some(&"Local")) | ("{AppData}", Some(&"LocalLow")) => Some("{XdgData}"),
+            _ => None,
+        };
+        match mapped {
+            Some(placeholder) => {
+                out.push(placeholder);
+                i += 2;
+            }
+            None => {
+                out.push(segments[i]);
+                i += 1;
+            }
+        }
+    }
+    out.join("\\")
+}
+
+/// 路径模板中支持的占位符
+/// `{ProtonPrefix}` 为游戏的 Proton 前缀（需要 Steam App ID），`{WinePrefix}` 为 `$WINEPREFIX`（默认 ~/.wine）
+/// `{XdgConfig}`、`{XdgData}` 为 Linux 的 `$XDG_CONFIG_HOME`（~/.config）与 `$XDG_DATA_HOME`（~/.local/share）
+pub const KNOWN_PLACEHOLDERS: &[&str] = &[
+    "{Steam}",
+    "{SteamUID}",
+    "{AppData}",
+    "{User}",
+    "{Home}",
+    "{XdgConfig}",
+    "{XdgData}",
+    "{ProtonPrefix}",
+    "{WinePrefix}",
+];
+
+/// 带参数的占位符前缀：`{SteamApp:<appid>}` 展开为该 Steam 应用的实际安装目录（可以在任意 Steam 库中）
+const STEAM_APP_PREFIX: &str = "{SteamApp:";
+
+fn is_known_placeholder(token: &str) -> bool {
+    if KNOWN_PLACEHOLDERS.contains(&token) {
+        return true;
+    }
+    token
+        .strip_prefix(STEAM_APP_PREFIX)
+        .and_then(|rest| rest.strip_suffix('}'))
+        .is_some_and(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// 读取 VDF/ACF 文件
+fn read_vdf(path: &Path) -> Result<vdf::VdfValue, String> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("读取 {} 失败: {e}", path.display()))?;
+    vdf::parse(&text).map_err(|e| format!("解析 {} 失败: {e}", path.display()))
+}
+
+/// 列出所有 Steam 库目录：Steam 安装目录本身，加上 `steamapps/libraryfolders.vdf` 中登记的其他库
+/// 新版格式为 `"0" { "path" "D:\\SteamLibrary" ... }`，旧版为 `"1" "D:\\SteamLibrary"`
+pub fn steam_library_folders(steam_dir: &Path) -> Vec<PathBuf> {
+    let mut folders = vec![steam_dir.to_path_buf()];
+    let Ok(root) = read_vdf(&steam_dir.join("steamapps").join("libraryfolders.vdf")) else {
+        return folders;
+    };
+    let libraries = root.get("libraryfolders").or_else(|| root.get("LibraryFolders"));
+    for (key, value) in libraries.map(|v| v.entries()).unwrap_or_default() {
+        let path = match value {
+            vdf::VdfValue::Map(_) => value.get("path").and_then(|p| p.as_str()),
+            vdf::VdfValue::Str(s) if key.chars().all(|c| c.is_ascii_digit()) => Some(s.as_str()),
+            vdf::VdfValue::Str(_) => None,
+        };
+        if let Some(path) = path.map(PathBuf::from) {
+            if !folders.contains(&path) {
+                folders.push(path);
+            }
+        }
+    }
+    folders
+}
+
+/// Proton 前缀中 Windows 用户目录的位置
+const PROTON_USER_DIR: &str = "{ProtonPrefix}\\drive_c\\users\\steamuser";
+
+/// 查找游戏的 Proton 前缀：各 Steam 库中的 `steamapps/compatdata/<appid>/pfx`
+pub fn find_proton_prefix(app_id: u32) -> Result<PathBuf, String> {
+    let steam_dir = get_steam_install_dir_internal()?;
+    steam_library_folders(Path::new(&steam_dir))
+        .into_iter()
+        .map(|library| {
+            library
+                .join("steamapps")
+                .join("compatdata")
+                .join(app_id.to_string())
+                .join("pfx")
+        })
+        .find(|prefix| prefix.is_dir())
+        .ok_or_else(|| format!("未找到 Steam 应用 {app_id} 的 Proton 前缀"))
+}
+
+/// Wine 前缀：`$WINEPREFIX`，未设置时为 `~/.wine`
+fn get_wine_prefix() -> Result<String, String> {
+    if let Some(prefix) = env::var_os("WINEPREFIX").filter(|v| Path::new(v).is_absolute()) {
+        return Ok(normalize_path_separators(prefix.to_string_lossy()));
+    }
+    let home = get_user_home()?;
+    Ok(normalize_path_separators(Path::new(&home).join(".wine").to_string_lossy()))
+}
+
+/// 把 Windows 用户目录相关的占位符改写到 Proton 前缀中：
+/// `{AppData}` 为 `steamuser\AppData`，`{Home}`、`{User}` 为 `steamuser`（其下的 Documents 等目录与 Windows 一致）
+fn map_windows_user_to_prefix(template: &str) -> String {
+    template
+        .replace("{AppData}", &format!("{PROTON_USER_DIR}\\AppData"))
+        .replace("{Home}", PROTON_USER_DIR)
+        .replace("{User}", PROTON_USER_DIR)
+}
+
+/// 模板中第一个 `{SteamApp:<appid>}` 的 app id
+fn steam_app_in_template(template: &str) -> Option<u32> {
+    let start = template.find(STEAM_APP_PREFIX)? + STEAM_APP_PREFIX.len();
+    let len = template[start..].find('}')?;
+    template[start..start + len].parse().ok()
+}
+
+/// 查找 Steam 应用的安装目录：在各个库的 `steamapps/appmanifest_<appid>.acf` 中读取 `installdir`
+pub fn find_steam_app_dir(app_id: &str) -> Result<PathBuf, String> {
+    find_steam_app_dir_in(Path::new(&get_steam_install_dir_internal()?), app_id)
+}
+
+fn find_steam_app_dir_in(steam_dir: &Path, app_id: &str) -> Result<PathBuf, String> {
+    for library in steam_library_folders(steam_dir) {
+        let steamapps = library.join("steamapps");
+        let manifest = steamapps.join(format!("appmanifest_{app_id}.acf"));
+        if !manifest.exists() {
+            continue;
+        }
+        let state = read_vdf(&manifest)?;
+        if let Some(dir) = state
+            .get("AppState")
+            .and_then(|s| s.get("installdir"))
+            .and_then(|d| d.as_str())
+        {
+            return Ok(steamapps.join("common").join(dir));
+        }
+    }
+    Err(format!("未找到 Steam 应用 {app_id} 的安装目录"))
+}
+
+/// 检查路径模板：不能为空，花括号需成对，且只能使用已知占位符
+pub fn validate_template(template: &str) -> Result<(), String> {
+    if template.trim().is_empty() {
+        return Err("存档路径不能为空".to_string());
+    }
+
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        if rest[..start].contains('}') {
+            return Err("路径中存在多余的右括号".to_string());
+        }
+        let Some(len) = rest[start..].find('}') else {
+            return Err(format!("路径中的占位符缺少右括号: {}", &rest[start..]));
+        };
+        let token = &rest[start..start + len + 1];
+        if !is_known_placeholder(token) {
+            return Err(format!("未知的占位符: {token}"));
+        }
+        rest = &rest[start + len + 1..];
+    }
+
+    if rest.contains('}') {
+        return Err("路径中存在多余的右括号".to_string());
+    }
+    Ok(())
 }
 
 /// 将模板路径中的占位符替换为实际路径
+/// `app_id` 为游戏的 Steam App ID（没有时取模板中的 `{SteamApp:<appid>}`），用于定位 Proton 前缀
 #[command]
-pub fn resolve_template_path(template: String, steam_uid: Option<String>) -> Result<PathBuf, String> {
+pub fn resolve_template_path(
+    template: String,
+    steam_uid: Option<String>,
+    app_id: Option<u32>,
+) -> Result<PathBuf, String> {
+    let app_id = app_id.or_else(|| steam_app_in_template(&template));
     let mut path_str = template.to_string();
 
+    while let Some(start) = path_str.find(STEAM_APP_PREFIX) {
+        let Some(len) = path_str[start..].find('}') else {
+            return Err("路径中的占位符缺少右括号".to_string());
+        };
+        let app_id = &path_str[start + STEAM_APP_PREFIX.len()..start + len];
+        let app_dir = find_steam_app_dir(app_id)?;
+        path_str.replace_range(start..start + len + 1, &app_dir.to_string_lossy());
+    }
+
     if path_str.contains("{Steam}") {
         let steam_dir = get_steam_install_dir_internal()?;
         path_str = path_str.replace("{Steam}", &steam_dir);
@@ -77,6 +325,34 @@ pub fn resolve_template_path(template: String, steam_uid: Option<String>) -> Res
         }
     }
 
+    if !cfg!(target_os = "windows") {
+        // 通过 Proton 运行的 Windows 游戏把存档写在前缀里；找不到前缀时按原生版本处理
+        let has_prefix = app_id.is_some_and(|id| find_proton_prefix(id).is_ok());
+        path_str = if has_prefix {
+            map_windows_user_to_prefix(&path_str)
+        } else {
+            map_appdata_to_xdg(&path_str)
+        };
+    }
+
+    if path_str.contains("{ProtonPrefix}") {
+        let app_id = app_id.ok_or_else(|| "缺少 Steam App ID，无法定位 Proton 前缀".to_string())?;
+        let prefix = find_proton_prefix(app_id)?;
+        path_str = path_str.replace("{ProtonPrefix}", &prefix.to_string_lossy());
+    }
+
+    if path_str.contains("{WinePrefix}") {
+        path_str = path_str.replace("{WinePrefix}", &get_wine_prefix()?);
+    }
+
+    if path_str.contains("{XdgConfig}") {
+        path_str = path_str.replace("{XdgConfig}", &get_xdg_dir("XDG_CONFIG_HOME", ".config")?);
+    }
+
+    if path_str.contains("{XdgData}") {
+        path_str = path_str.replace("{XdgData}", &get_xdg_dir("XDG_DATA_HOME", ".local/share")?);
+    }
+
     if path_str.contains("{AppData}") {
         let appdata = get_appdata_root()?;
         path_str = path_str.replace("{AppData}", &appdata);
@@ -94,15 +370,41 @@ pub fn resolve_template_path(template: String, steam_uid: Option<String>) -> Res
     Ok(PathBuf::from(normalized))
 }
 
-/// 列出 Steam UID 目录名（纯数字）
-pub fn list_steam_uid() -> Vec<String> {
-    // 失败场景直接返回空列表，避免层层嵌套
-    let Ok(steam_dir) = get_steam_install_dir_internal() else {
-        return Vec::new();
-    };
+/// 个人账号 SteamID64 与 32 位账号 id（userdata 目录名）之间的固定差值
+const STEAM_ID64_BASE: u64 = 76561197960265728;
+
+/// SteamID64 转为 32 位账号 id；不是个人账号的 id 返回 None
+pub fn steam_id64_to_account_id(steam_id64: u64) -> Option<u32> {
+    steam_id64
+        .checked_sub(STEAM_ID64_BASE)
+        .and_then(|id| u32::try_from(id).ok())
+}
+
+/// 32 位账号 id 转为 SteamID64
+pub fn account_id_to_steam_id64(account_id: u32) -> u64 {
+    STEAM_ID64_BASE + u64::from(account_id)
+}
 
-    let userdata = Path::new(&steam_dir).join("userdata");
-    let entries = match fs::read_dir(&userdata) {
+/// 本机的 Steam 账号：以 userdata 下的目录为准，登录信息来自 `config/loginusers.vdf`
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SteamUser {
+    /// 32 位账号 id，即 `{SteamUID}` 的取值
+    pub account_id: String,
+    /// SteamID64（超出 JS 安全整数范围，用字符串传给前端）
+    pub steam_id64: String,
+    /// 昵称与登录名；没有登录记录时为空
+    pub persona_name: Option<String>,
+    pub account_name: Option<String>,
+    /// 是否为最近一次登录的账号
+    pub most_recent: bool,
+    /// 最近登录时间（秒）
+    pub timestamp: Option<i64>,
+}
+
+/// userdata 下的账号目录名（纯数字）
+fn steam_uids_in(steam_dir: &Path) -> Vec<String> {
+    let entries = match fs::read_dir(steam_dir.join("userdata")) {
         Ok(it) => it,
         Err(_) => return Vec::new(),
     };
@@ -121,3 +423,337 @@ pub fn list_steam_uid() -> Vec<String> {
         })
         .collect()
 }
+
+/// 列出 Steam 目录下的账号，最近登录的排在最前，其余按登录时间从新到旧、没有登录记录的排在最后
+fn steam_users_in(steam_dir: &Path) -> Vec<SteamUser> {
+    // loginusers.vdf 读不到时只是缺少昵称，不影响列出账号
+    let logins = read_vdf(&steam_dir.join("config").join("loginusers.vdf")).ok();
+    let login_of = |account_id: u32| {
+        let id64 = account_id_to_steam_id64(account_id).to_string();
+        logins.as_ref()?.get("users")?.get(&id64).cloned()
+    };
+
+    let mut users: Vec<SteamUser> = steam_uids_in(steam_dir)
+        .into_iter()
+        .filter_map(|uid| {
+            let account_id: u32 = uid.parse().ok()?;
+            let login = login_of(account_id);
+            let field = |key: &str| {
+                login
+                    .as_ref()
+                    .and_then(|l| l.get(key))
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string)
+            };
+            Some(SteamUser {
+                steam_id64: account_id_to_steam_id64(account_id).to_string(),
+                persona_name: field("PersonaName"),
+                account_name: field("AccountName"),
+                most_recent: field("MostRecent").is_some_and(|v| v == "1"),
+                timestamp: field("Timestamp").and_then(|v| v.parse().ok()),
+                account_id: uid,
+            })
+        })
+        .collect();
+    users.sort_by(|a, b| {
+        b.most_recent
+            .cmp(&a.most_recent)
+            .then(b.timestamp.cmp(&a.timestamp))
+            .then(a.account_id.cmp(&b.account_id))
+    });
+    users
+}
+
+/// 列出本机的 Steam 账号（找不到 Steam 时返回空列表）
+pub fn list_steam_users() -> Vec<SteamUser> {
+    match get_steam_install_dir_internal() {
+        Ok(steam_dir) => steam_users_in(Path::new(&steam_dir)),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// 列出 Steam UID 目录名（纯数字），顺序与 `list_steam_users` 一致
+pub fn list_steam_uid() -> Vec<String> {
+    list_steam_users().into_iter().map(|u| u.account_id).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsString;
+    use std::sync::{Mutex, MutexGuard};
+
+    /// 修改环境变量的测试之间互斥（目前只有非 Windows 平台的测试用到）
+    #[cfg_attr(target_os = "windows", allow(dead_code))]
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    /// 临时修改环境变量：持有全局锁，结束时（包括断言失败）恢复原值
+    #[cfg_attr(target_os = "windows", allow(dead_code))]
+    struct EnvGuard {
+        saved: Vec<(&'static str, Option<OsString>)>,
+        _lock: MutexGuard<'static, ()>,
+    }
+
+    impl EnvGuard {
+        #[cfg_attr(target_os = "windows", allow(dead_code))]
+        fn new(vars: &[&'static str]) -> Self {
+            let lock = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            EnvGuard {
+                saved: vars.iter().map(|&var| (var, env::var_os(var))).collect(),
+                _lock: lock,
+            }
+        }
+    }
+
+    impl Drop for EnvGuard {
+        fn drop(&mut self) {
+            for (var, value) in &self.saved {
+                match value {
+                    Some(value) => env::set_var(var, value),
+                    None => env::remove_var(var),
+                }
+            }
+        }
+    }
+
+    /// 每个测试使用独立的临时目录
+    fn temp_home(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("game-sl-paths-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn normalize_separators_for_each_platform() {
+        assert_eq!(normalize_separators_to("C:/Users\\\\me//Saves", '\\'), "C:\\Users\\me\\Saves");
+        assert_eq!(normalize_separators_to("/home/deck\\.config\\\\Game", '/'), "/home/deck/.config/Game");
+        assert_eq!(normalize_separators_to("", '/'), "");
+    }
+
+    #[test]
+    fn appdata_maps_to_xdg() {
+        assert_eq!(map_appdata_to_xdg("{AppData}\\Roaming\\EldenRing"), "{XdgConfig}\\EldenRing");
+        assert_eq!(map_appdata_to_xdg("{AppData}/LocalLow/Team Cherry"), "{XdgData}\\Team Cherry");
+        assert_eq!(map_appdata_to_xdg("{AppData}\\Local"), "{XdgData}");
+        assert_eq!(map_appdata_to_xdg("{AppData}\\Other"), "{AppData}\\Other");
+        assert_eq!(map_appdata_to_xdg("{Home}\\Roaming"), "{Home}\\Roaming");
+    }
+
+    #[test]
+    fn find_steam_dir_in_candidates() {
+        let home = temp_home("steam");
+        assert_eq!(find_steam_dir_in(&home), None);
+
+        let flatpak = home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam");
+        fs::create_dir_all(flatpak.join("steamapps")).unwrap();
+        assert_eq!(find_steam_dir_in(&home), Some(flatpak.canonicalize().unwrap()));
+
+        // 原生安装优先于 Flatpak
+        let native = home.join(".local/share/Steam");
+        fs::create_dir_all(native.join("steamapps")).unwrap();
+        assert_eq!(find_steam_dir_in(&home), Some(native.canonicalize().unwrap()));
+
+        let _ = fs::remove_dir_all(&home);
+    }
+
+    #[test]
+    fn steam_library_folders_from_vdf() {
+        let steam = temp_home("libraries");
+        fs::create_dir_all(steam.join("steamapps")).unwrap();
+        fs::write(
+            steam.join("steamapps/libraryfolders.vdf"),
+            r#""libraryfolders"
+{
+	"0" { "path" "/home/deck/.local/share/Steam" }
+	"1" { "path" "/run/media/mmcblk0p1" }
+	"contentstatsid" "123"
+}"#,
+        )
+        .unwrap();
+        let folders = steam_library_folders(&steam);
+        assert_eq!(
+            folders,
+            vec![
+                steam.clone(),
+                PathBuf::from("/home/deck/.local/share/Steam"),
+                PathBuf::from("/run/media/mmcblk0p1"),
+            ]
+        );
+        let _ = fs::remove_dir_all(&steam);
+    }
+
+    #[test]
+    fn steam_library_folders_from_legacy_vdf() {
+        let steam = temp_home("legacy-libraries");
+        fs::create_dir_all(steam.join("steamapps")).unwrap();
+        fs::write(
+            steam.join("steamapps/libraryfolders.vdf"),
+            r#""LibraryFolders"
+{
+	"TimeNextStatsReport"		"1700000000"
+	"ContentStatsID"		"-123"
+	"1"		"D:\\SteamLibrary"
+}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            steam_library_folders(&steam),
+            vec![steam.clone(), PathBuf::from("D:\\SteamLibrary")]
+        );
+        let _ = fs::remove_dir_all(&steam);
+    }
+
+    #[test]
+    fn find_steam_app_dir_across_libraries() {
+        let steam = temp_home("app-dir");
+        let library = temp_home("app-dir-library");
+        fs::create_dir_all(steam.join("steamapps")).unwrap();
+        fs::create_dir_all(library.join("steamapps")).unwrap();
+        // VDF 字符串中的反斜杠需要转义（Windows 路径）
+        let quoted = |p: &Path| p.display().to_string().replace('\\', "\\\\");
+        fs::write(
+            steam.join("steamapps/libraryfolders.vdf"),
+            format!(
+                "\"libraryfolders\"\n{{\n\t\"0\" {{ \"path\" \"{}\" }}\n\t\"1\" {{ \"path\" \"{}\" }}\n}}",
+                quoted(&steam),
+                quoted(&library)
+            ),
+        )
+        .unwrap();
+        fs::write(
+            steam.join("steamapps/appmanifest_10.acf"),
+            "\"AppState\"\n{\n\t\"appid\" \"10\"\n\t\"installdir\" \"Counter-Strike\"\n}",
+        )
+        .unwrap();
+        fs::write(
+            library.join("steamapps/appmanifest_367520.acf"),
+            "\"AppState\"\n{\n\t\"appid\" \"367520\"\n\t\"installdir\" \"Hollow Knight\"\n}",
+        )
+        .unwrap();
+
+        assert_eq!(
+            find_steam_app_dir_in(&steam, "10").unwrap(),
+            steam.join("steamapps/common/Counter-Strike")
+        );
+        assert_eq!(
+            find_steam_app_dir_in(&steam, "367520").unwrap(),
+            library.join("steamapps/common/Hollow Knight")
+        );
+        assert!(find_steam_app_dir_in(&steam, "1").is_err());
+
+        let _ = fs::remove_dir_all(&steam);
+        let _ = fs::remove_dir_all(&library);
+    }
+
+    #[test]
+    fn validate_known_placeholders() {
+        assert!(validate_template("{XdgConfig}\\unity3d\\Game").is_ok());
+        assert!(validate_template("{SteamApp:2358720}\\b1").is_ok());
+        assert!(validate_template("{ProtonPrefix}\\drive_c").is_ok());
+        assert_eq!(steam_app_in_template("{SteamApp:2358720}\\b1"), Some(2358720));
+        assert_eq!(steam_app_in_template("{Steam}\\b1"), None);
+        assert!(validate_template("{SteamApp:abc}").is_err());
+        assert!(validate_template("{Unknown}\\x").is_err());
+    }
+
+    #[test]
+    fn convert_steam_ids() {
+        assert_eq!(steam_id64_to_account_id(76561198000000000), Some(39734272));
+        assert_eq!(account_id_to_steam_id64(39734272), 76561198000000000);
+        assert_eq!(steam_id64_to_account_id(12345), None);
+    }
+
+    #[test]
+    fn steam_users_prefer_most_recent() {
+        let steam = temp_home("users");
+        for uid in ["1", "39734272", "100", "not-a-uid"] {
+            fs::create_dir_all(steam.join("userdata").join(uid)).unwrap();
+        }
+        fs::create_dir_all(steam.join("config")).unwrap();
+        fs::write(
+            steam.join("config/loginusers.vdf"),
+            r#""users"
+{
+	"76561197960265828"
+	{
+		"AccountName"		"older"
+		"PersonaName"		"Older"
+		"MostRecent"		"0"
+		"Timestamp"		"1600000000"
+	}
+	"76561198000000000"
+	{
+		"AccountName"		"main"
+		"PersonaName"		"Main"
+		"MostRecent"		"1"
+		"Timestamp"		"1500000000"
+	}
+}"#,
+        )
+        .unwrap();
+
+        let users = steam_users_in(&steam);
+        let ids: Vec<&str> = users.iter().map(|u| u.account_id.as_str()).collect();
+        assert_eq!(ids, vec!["39734272", "100", "1"]);
+        assert_eq!(users[0].persona_name.as_deref(), Some("Main"));
+        assert_eq!(users[0].steam_id64, "76561198000000000");
+        assert!(users[0].most_recent);
+        assert_eq!(users[1].account_name.as_deref(), Some("older"));
+        assert_eq!(users[2].persona_name, None);
+
+        let _ = fs::remove_dir_all(&steam);
+    }
+
+    /// 会修改 HOME 等环境变量，相关断言集中在这一个测试里，结束时恢复原值
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn resolve_template_on_linux() {
+        let _env = EnvGuard::new(&[
+            "USERPROFILE",
+            "HOME",
+            "XDG_CONFIG_HOME",
+            "XDG_DATA_HOME",
+            "WINEPREFIX",
+        ]);
+        let home = temp_home("resolve");
+        env::remove_var("USERPROFILE");
+        env::set_var("HOME", &home);
+        env::remove_var("XDG_CONFIG_HOME");
+        env::set_var("XDG_DATA_HOME", "relative/ignored");
+
+        let resolved = resolve_template_path("{Home}\\Documents\\Game".into(), None, None).unwrap();
+        assert_eq!(resolved, home.join("Documents/Game"));
+
+        let resolved = resolve_template_path("{AppData}\\Roaming\\EldenRing".into(), None, None).unwrap();
+        assert_eq!(resolved, home.join(".config/EldenRing"));
+
+        let resolved = resolve_template_path("{AppData}\\LocalLow\\Team Cherry".into(), None, None).unwrap();
+        assert_eq!(resolved, home.join(".local/share/Team Cherry"));
+
+        fs::create_dir_all(home.join(".steam/steam/steamapps")).unwrap();
+        let steam = home.join(".steam/steam").canonicalize().unwrap();
+        let resolved =
+            resolve_template_path("{Steam}\\userdata\\{SteamUID}\\1\\remote".into(), Some("42".into()), None)
+                .unwrap();
+        assert_eq!(resolved, steam.join("userdata/42/1/remote"));
+
+        // 有 Proton 前缀时 Windows 模板指向前缀内的用户目录
+        let prefix = steam.join("steamapps/compatdata/1245620/pfx");
+        fs::create_dir_all(&prefix).unwrap();
+        let resolved =
+            resolve_template_path("{AppData}\\Roaming\\EldenRing".into(), None, Some(1245620)).unwrap();
+        assert_eq!(resolved, prefix.join("drive_c/users/steamuser/AppData/Roaming/EldenRing"));
+        let resolved = resolve_template_path("{Home}\\Documents\\My Games".into(), None, Some(1245620)).unwrap();
+        assert_eq!(resolved, prefix.join("drive_c/users/steamuser/Documents/My Games"));
+        let resolved = resolve_template_path("{ProtonPrefix}\\drive_c".into(), None, Some(1245620)).unwrap();
+        assert_eq!(resolved, prefix.join("drive_c"));
+        assert!(resolve_template_path("{ProtonPrefix}".into(), None, None).is_err());
+
+        env::remove_var("WINEPREFIX");
+        let resolved = resolve_template_path("{WinePrefix}\\drive_c".into(), None, None).unwrap();
+        assert_eq!(resolved, home.join(".wine/drive_c"));
+
+        let _ = fs::remove_dir_all(&home);
+    }
+}
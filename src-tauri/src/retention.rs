@@ -0,0 +1,424 @@
+use crate::backup::{self, BackupEntry, BackupFormat};
+use crate::config;
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// 备份保留策略；各项均为空时保留全部备份
+/// 全局策略写在 settings.retention，单个游戏可在 GameEntry.retention 中整体覆盖
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionPolicy {
+    /// 保留最近 N 份（不含已锁定的备份，见 `select_prunable`）
+    pub keep_last: Option<usize>,
+    /// 最近 N 天中每天保留最新的一份
+    pub keep_daily: Option<usize>,
+    /// 最近 N 周中每周保留最新的一份
+    pub keep_weekly: Option<usize>,
+    /// 最近 N 个月中每月保留最新的一份
+    pub keep_monthly: Option<usize>,
+    /// 备份总大小上限（字节）；超出时从最旧的开始清理，最新的一份始终保留
+    /// 去重快照之间共用存储，单份快照的大小不代表实际占用，因此不计入总大小，也不会因此被清理
+    pub max_total_size: Option<u64>,
+}
+
+impl RetentionPolicy {
+    /// 各项保留份数与大小上限都不能为 0（为 0 会把全部未锁定的备份清理掉）
+    pub fn validate(&self) -> Result<(), String> {
+        let counts = [self.keep_last, self.keep_daily, self.keep_weekly, self.keep_monthly];
+        if counts.contains(&Some(0)) {
+            return Err("保留份数至少为 1".to_string());
+        }
+        if self.max_total_size == Some(0) {
+            return Err("备份总大小上限必须大于 0".to_string());
+        }
+        Ok(())
+    }
+
+    fn has_count_rules(&self) -> bool {
+        self.keep_last.is_some()
+            || self.keep_daily.is_some()
+            || self.keep_weekly.is_some()
+            || self.keep_monthly.is_some()
+    }
+
+    fn is_empty(&self) -> bool {
+        !self.has_count_rules() && self.max_total_size.is_none()
+    }
+}
+
+/// 将被清理的备份
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PruneCandidate {
+    pub file_name: String,
+    pub timestamp: Option<i64>,
+    pub size: u64,
+    /// 清理原因：`policy`（不在任何保留规则内）或 `size-limit`（超出总大小上限）
+    pub reason: String,
+}
+
+/// 读取指定游戏生效的保留策略：游戏自己的配置优先，否则使用全局配置
+/// 游戏的策略可能是手动改出来的，无效时拒绝清理，避免把备份全部删掉
+pub fn policy_for_game(game_id: &str) -> Result<RetentionPolicy, String> {
+    let config = config::read_config()?;
+    let game = config::find_game(&config, game_id)?;
+    if let Some(policy) = &game.retention {
+        policy
+            .validate()
+            .map_err(|e| format!("游戏 {} 的保留策略无效: {e}", game.name))?;
+        return Ok(policy.clone());
+    }
+    Ok(config.settings.retention.unwrap_or_default())
+}
+
+/// 按时间桶保留：从新到旧遍历，每个新出现的桶保留一份，直到保留了 `limit` 个桶
+fn keep_by_bucket(
+    entries: &[&BackupEntry],
+    limit: usize,
+    bucket: impl Fn(i64) -> Option<String>,
+    keep: &mut HashSet<String>,
+) {
+    let mut seen = HashSet::new();
+    for entry in entries {
+        if seen.len() >= limit {
+            break;
+        }
+        let Some(key) = entry.timestamp.and_then(&bucket) else {
+            continue;
+        };
+        if seen.insert(key) {
+            keep.insert(entry.file_name.clone());
+        }
+    }
+}
+
+fn format_local(ts: i64, fmt: &str) -> Option<String> {
+    Local
+        .timestamp_millis_opt(ts)
+        .single()
+        .map(|dt| dt.format(fmt).to_string())
+}
+
+/// 计算需要清理的备份；`entries` 需按时间从新到旧排列（与 `list_backups` 的顺序一致）
+/// 已锁定的备份与读不到时间的备份始终保留（锁定的备份仍计入总大小，去重快照不参与大小上限）
+/// 锁定的备份不占用各项规则的份数：锁定一份旧备份不会让最近保留的备份变少
+pub fn select_prunable(entries: &[BackupEntry], policy: &RetentionPolicy) -> Vec<PruneCandidate> {
+    if policy.is_empty() {
+        return Vec::new();
+    }
+
+    let mut keep: HashSet<String> = entries
+        .iter()
+        .filter(|e| e.pinned || e.timestamp.is_none())
+        .map(|e| e.file_name.clone())
+        .collect();
+
+    if policy.has_count_rules() {
+        let unpinned: Vec<&BackupEntry> = entries.iter().filter(|e| !e.pinned).collect();
+        if let Some(n) = policy.keep_last {
+            keep.extend(unpinned.iter().take(n).map(|e| e.file_name.clone()));
+        }
+        if let Some(n) = policy.keep_daily {
+            keep_by_bucket(&unpinned, n, |ts| format_local(ts, "%Y-%m-%d"), &mut keep);
+        }
+        if let Some(n) = policy.keep_weekly {
+            keep_by_bucket(&unpinned, n, |ts| format_local(ts, "%G-W%V"), &mut keep);
+        }
+        if let Some(n) = policy.keep_monthly {
+            keep_by_bucket(&unpinned, n, |ts| format_local(ts, "%Y-%m"), &mut keep);
+        }
+    } else {
+        // 只配置了大小上限
+        keep.extend(entries.iter().map(|e| e.file_name.clone()));
+    }
+
+    let mut prune: Vec<PruneCandidate> = Vec::new();
+    let mut total: u64 = 0;
+    for (i, entry) in entries.iter().enumerate() {
+        let is_snapshot = BackupFormat::from_path(Path::new(&entry.file_name))
+            == Some(BackupFormat::Snapshot);
+        let reason = if !keep.contains(&entry.file_name) {
+            Some("policy")
+        } else if is_snapshot {
+            None
+        } else {
+            total += entry.size;
+            match policy.max_total_size {
+                Some(max) if i > 0 && total > max && entry.timestamp.is_some() && !entry.pinned => {
+                    total -= entry.size;
+                    Some("size-limit")
+                }
+                _ => None,
+            }
+        };
+
+        if let Some(reason) = reason {
+            prune.push(PruneCandidate {
+                file_name: entry.file_name.clone(),
+                timestamp: entry.timestamp,
+                size: entry.size,
+                reason: reason.to_string(),
+            });
+        }
+    }
+    prune
+}
+
+/// 按保留策略清理指定游戏的备份；`dry_run` 为 true 时只返回将被清理的列表
+pub fn prune_backups(game_id: &str, dry_run: bool) -> Result<Vec<PruneCandidate>, String> {
+    let policy = policy_for_game(game_id)?;
+    let entries = backup::list_backups(game_id.to_string())?;
+    let candidates = select_prunable(&entries, &policy);
+
+    if !dry_run {
+        for c in &candidates {
+            backup::delete_backup(game_id.to_string(), c.file_name.clone(), false)?;
+        }
+    }
+    Ok(candidates)
+}
+
+/// 备份完成后按保留策略清理：逐个删除，某个备份删除失败时记下原因并继续
+/// 返回已删除的备份文件名与失败信息
+pub fn prune_after_backup(game_id: &str) -> (Vec<String>, Vec<String>) {
+    let candidates = match prune_backups(game_id, true) {
+        Ok(candidates) => candidates,
+        Err(e) => return (Vec::new(), vec![format!("计算需要清理的备份失败: {e}")]),
+    };
+
+    let mut pruned = Vec::new();
+    let mut errors = Vec::new();
+    for c in candidates {
+        match backup::delete_backup(game_id.to_string(), c.file_name.clone(), false) {
+            Ok(()) => pruned.push(c.file_name),
+            Err(e) => errors.push(format!("{}: {e}", c.file_name)),
+        }
+    }
+    (pruned, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::VerifyStatus;
+
+    /// 本地时间 `y-m-d h:00` 的毫秒时间戳，时间桶按本地时间划分
+    fn at(y: i32, m: u32, d: u32, h: u32) -> Option<i64> {
+        Local
+            .with_ymd_and_hms(y, m, d, h, 0, 0)
+            .single()
+            .map(|dt| dt.timestamp_millis())
+    }
+
+    fn entry(name: &str, timestamp: Option<i64>, size: u64) -> BackupEntry {
+        BackupEntry {
+            file_name: name.to_string(),
+            file_path: name.to_string(),
+            timestamp,
+            remark: None,
+            size,
+            time_source: "filename".to_string(),
+            manifest: None,
+            verify_status: VerifyStatus::Unverified,
+            pinned: false,
+        }
+    }
+
+    fn pinned(mut e: BackupEntry) -> BackupEntry {
+        e.pinned = true;
+        e
+    }
+
+    /// 清理的文件名（按 `entries` 的顺序）
+    fn pruned(entries: &[BackupEntry], policy: &RetentionPolicy) -> Vec<String> {
+        select_prunable(entries, policy)
+            .into_iter()
+            .map(|c| c.file_name)
+            .collect()
+    }
+
+    #[test]
+    fn empty_policy_keeps_everything() {
+        let entries = [
+            entry("a.zip", at(2025, 1, 2, 12), 10),
+            entry("b.zip", at(2025, 1, 1, 12), 10),
+        ];
+        assert!(pruned(&entries, &RetentionPolicy::default()).is_empty());
+    }
+
+    #[test]
+    fn count_rules() {
+        // 2025-01-13 是周一；同一天、同一周、同一月各有多份
+        let entries = [
+            entry("0.zip", at(2025, 1, 15, 18), 1),
+            entry("1.zip", at(2025, 1, 15, 9), 1),
+            entry("2.zip", at(2025, 1, 14, 9), 1),
+            entry("3.zip", at(2025, 1, 12, 9), 1),
+            entry("4.zip", at(2025, 1, 3, 9), 1),
+            entry("5.zip", at(2024, 12, 20, 9), 1),
+            entry("6.zip", at(2024, 11, 20, 9), 1),
+        ];
+        let cases: [(RetentionPolicy, &[&str]); 5] = [
+            (
+                RetentionPolicy {
+                    keep_last: Some(2),
+                    ..Default::default()
+                },
+                &["2.zip", "3.zip", "4.zip", "5.zip", "6.zip"],
+            ),
+            (
+                RetentionPolicy {
+                    keep_daily: Some(3),
+                    ..Default::default()
+                },
+                &["1.zip", "4.zip", "5.zip", "6.zip"],
+            ),
+            (
+                RetentionPolicy {
+                    keep_weekly: Some(2),
+                    ..Default::default()
+                },
+                &["1.zip", "2.zip", "4.zip", "5.zip", "6.zip"],
+            ),
+            (
+                RetentionPolicy {
+                    keep_monthly: Some(2),
+                    ..Default::default()
+                },
+                &["1.zip", "2.zip", "3.zip", "4.zip", "6.zip"],
+            ),
+            // 各规则取并集
+            (
+                RetentionPolicy {
+                    keep_last: Some(2),
+                    keep_monthly: Some(3),
+                    ..Default::default()
+                },
+                &["2.zip", "3.zip", "4.zip"],
+            ),
+        ];
+        for (policy, expected) in cases {
+            assert_eq!(pruned(&entries, &policy), expected, "{policy:?}");
+        }
+    }
+
+    #[test]
+    fn size_limit_prunes_oldest_but_keeps_newest() {
+        let entries = [
+            entry("0.zip", at(2025, 1, 3, 9), 60),
+            entry("1.zip", at(2025, 1, 2, 9), 30),
+            entry("2.zip", at(2025, 1, 1, 9), 30),
+        ];
+        let policy = RetentionPolicy {
+            max_total_size: Some(100),
+            ..Default::default()
+        };
+        let candidates = select_prunable(&entries, &policy);
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].file_name, "2.zip");
+        assert_eq!(candidates[0].reason, "size-limit");
+
+        // 最新的一份即使单独超出上限也保留
+        let policy = RetentionPolicy {
+            max_total_size: Some(10),
+            ..Default::default()
+        };
+        assert_eq!(pruned(&entries, &policy), ["1.zip", "2.zip"]);
+    }
+
+    #[test]
+    fn size_limit_applies_after_count_rules() {
+        let entries = [
+            entry("0.zip", at(2025, 1, 3, 9), 60),
+            entry("1.zip", at(2025, 1, 2, 9), 60),
+            entry("2.zip", at(2025, 1, 1, 9), 1),
+        ];
+        let policy = RetentionPolicy {
+            keep_last: Some(2),
+            max_total_size: Some(100),
+            ..Default::default()
+        };
+        let reasons: Vec<(String, String)> = select_prunable(&entries, &policy)
+            .into_iter()
+            .map(|c| (c.file_name, c.reason))
+            .collect();
+        assert_eq!(
+            reasons,
+            [
+                ("1.zip".to_string(), "size-limit".to_string()),
+                ("2.zip".to_string(), "policy".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn pinned_backups_are_kept_without_using_slots() {
+        let entries = [
+            pinned(entry("0.zip", at(2025, 1, 4, 9), 50)),
+            entry("1.zip", at(2025, 1, 3, 9), 50),
+            entry("2.zip", at(2025, 1, 2, 9), 50),
+            pinned(entry("3.zip", at(2025, 1, 1, 9), 50)),
+        ];
+        let policy = RetentionPolicy {
+            keep_last: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(pruned(&entries, &policy), ["2.zip"]);
+
+        let policy = RetentionPolicy {
+            keep_daily: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(pruned(&entries, &policy), ["2.zip"]);
+
+        // 锁定的备份计入总大小，但自身不会因此被清理
+        let policy = RetentionPolicy {
+            max_total_size: Some(120),
+            ..Default::default()
+        };
+        assert_eq!(pruned(&entries, &policy), ["2.zip"]);
+    }
+
+    #[test]
+    fn backups_without_timestamp_are_kept() {
+        let entries = [
+            entry("0.zip", at(2025, 1, 3, 9), 50),
+            entry("manual.zip", None, 50),
+            entry("1.zip", at(2025, 1, 2, 9), 50),
+        ];
+        let policy = RetentionPolicy {
+            keep_last: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(pruned(&entries, &policy), ["1.zip"]);
+
+        let policy = RetentionPolicy {
+            max_total_size: Some(60),
+            ..Default::default()
+        };
+        assert_eq!(pruned(&entries, &policy), ["1.zip"]);
+    }
+
+    #[test]
+    fn snapshots_are_exempt_from_size_limit() {
+        let entries = [
+            entry("0.zip", at(2025, 1, 3, 9), 50),
+            entry("1.snapshot", at(2025, 1, 2, 9), 500),
+            entry("2.zip", at(2025, 1, 1, 9), 50),
+        ];
+        let policy = RetentionPolicy {
+            max_total_size: Some(100),
+            ..Default::default()
+        };
+        assert!(pruned(&entries, &policy).is_empty());
+
+        // 快照仍受份数规则约束
+        let policy = RetentionPolicy {
+            keep_last: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(pruned(&entries, &policy), ["1.snapshot", "2.zip"]);
+    }
+}
@@ -0,0 +1,182 @@
+use crate::backup::BackupFormat;
+use crate::retention::RetentionPolicy;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+use std::path::Path;
+
+/// config.json 中的 settings：已知设置项有固定类型与默认值，其余键原样保留（例如新版本写入的设置）
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Settings {
+    /// 列表中使用相对时间（例如“3 分钟前”）
+    pub use_relative_time: bool,
+    /// 复原前额外备份现有存档
+    pub restore_extra_backup: bool,
+    /// 新建备份使用的格式
+    pub backup_format: BackupFormat,
+    /// 额外写出同名清单副本（清单默认只内嵌在压缩包里）
+    pub manifest_sidecar: bool,
+    /// 全局备份保留策略（游戏可单独覆盖）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retention: Option<RetentionPolicy>,
+    /// 备份根目录（为空时使用工作目录下的 backup），通过 `set_backup_location` 修改
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backup_root: Option<String>,
+    /// 未识别的设置项
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+    /// 读取时类型不对或没通过校验的已知设置项原值：程序按默认值处理，写回配置文件时原样保留（见 `to_file_value`）
+    #[serde(skip)]
+    pub invalid: Map<String, Value>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            use_relative_time: true,
+            restore_extra_backup: true,
+            backup_format: BackupFormat::Zip,
+            manifest_sidecar: false,
+            retention: None,
+            backup_root: None,
+            extra: Map::new(),
+            invalid: Map::new(),
+        }
+    }
+}
+
+impl Settings {
+    /// 写入配置文件的内容：无效的原值替换对应设置项，手动改错的值不会因为写回配置而被悄悄删掉
+    pub fn to_file_value(&self) -> Result<Value, String> {
+        let mut value = serde_json::to_value(self).map_err(|e| format!("序列化设置失败: {e}"))?;
+        if let Some(map) = value.as_object_mut() {
+            map.extend(self.invalid.clone());
+        }
+        Ok(value)
+    }
+}
+
+/// 取出并解析已知设置项；缺失（或为 null）时返回 `None`，类型不对时把原值放进 `invalid`
+fn take<T: DeserializeOwned>(
+    map: &mut Map<String, Value>,
+    invalid: &mut Map<String, Value>,
+    key: &str,
+) -> Option<T> {
+    take_checked(map, invalid, key, |_| true)
+}
+
+/// 同 `take`，另外要求值通过 `valid` 检查
+fn take_checked<T: DeserializeOwned>(
+    map: &mut Map<String, Value>,
+    invalid: &mut Map<String, Value>,
+    key: &str,
+    valid: impl Fn(&T) -> bool,
+) -> Option<T> {
+    let raw = map.remove(key).filter(|v| !v.is_null())?;
+    match serde_json::from_value(raw.clone()) {
+        Ok(value) if valid(&value) => Some(value),
+        _ => {
+            invalid.insert(key.to_string(), raw);
+            None
+        }
+    }
+}
+
+/// 读取时比较宽松：已知设置项无效时按默认值处理（原值保留在 `invalid` 中），手动改坏一个值不至于让配置整个读不出来
+impl<'de> Deserialize<'de> for Settings {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut map = Map::<String, Value>::deserialize(deserializer)?;
+        let mut invalid = Map::new();
+        let defaults = Settings::default();
+        Ok(Settings {
+            use_relative_time: take(&mut map, &mut invalid, "useRelativeTime")
+                .unwrap_or(defaults.use_relative_time),
+            restore_extra_backup: take(&mut map, &mut invalid, "restoreExtraBackup")
+                .unwrap_or(defaults.restore_extra_backup),
+            backup_format: take(&mut map, &mut invalid, "backupFormat")
+                .unwrap_or(defaults.backup_format),
+            manifest_sidecar: take(&mut map, &mut invalid, "manifestSidecar")
+                .unwrap_or(defaults.manifest_sidecar),
+            retention: take_checked(&mut map, &mut invalid, "retention", |p: &RetentionPolicy| {
+                p.validate().is_ok()
+            }),
+            // 与 `set_backup_location` 一致：相对路径会随工作目录变化，不能作为备份目录
+            backup_root: take_checked(&mut map, &mut invalid, "backupRoot", |p: &String| {
+                Path::new(p).is_absolute()
+            }),
+            extra: map,
+            invalid,
+        })
+    }
+}
+
+/// 检查单个设置项的值：已知设置项必须是对应的类型，未知设置项不做限制
+pub fn validate_setting(key: &str, value: &Value) -> Result<(), String> {
+    let checked = match key {
+        "useRelativeTime" | "restoreExtraBackup" | "manifestSidecar" => {
+            serde_json::from_value::<bool>(value.clone()).map(|_| ())
+        }
+        "backupFormat" => serde_json::from_value::<BackupFormat>(value.clone()).map(|_| ()),
+        "retention" => {
+            return match serde_json::from_value::<Option<RetentionPolicy>>(value.clone()) {
+                Ok(Some(policy)) => policy.validate(),
+                Ok(None) => Ok(()),
+                Err(e) => Err(format!("设置项 {key} 的值无效: {e}")),
+            }
+        }
+        // 直接改设置不会移动已有备份，必须走 set_backup_location
+        "backupRoot" => return Err("备份目录请通过“修改备份位置”设置，以便迁移已有备份".to_string()),
+        _ => Ok(()),
+    };
+    checked.map_err(|e| format!("设置项 {key} 的值无效: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn invalid_known_settings_fall_back_and_are_kept() {
+        let raw = json!({
+            "useRelativeTime": "yes",
+            "backupFormat": "rar",
+            "retention": { "keepLast": 0 },
+            "backupRoot": "relative/backup",
+            "manifestSidecar": true,
+            "futureKey": 1,
+        });
+        let settings: Settings = serde_json::from_value(raw.clone()).unwrap();
+
+        assert!(settings.use_relative_time);
+        assert_eq!(settings.backup_format, BackupFormat::Zip);
+        assert!(settings.retention.is_none());
+        assert!(settings.backup_root.is_none());
+        assert!(settings.manifest_sidecar);
+        assert_eq!(settings.extra["futureKey"], 1);
+        assert_eq!(settings.invalid.len(), 4);
+
+        // 返回给前端的是默认值，写回文件的是原值
+        let for_frontend = serde_json::to_value(&settings).unwrap();
+        assert_eq!(for_frontend["useRelativeTime"], true);
+        assert!(for_frontend.get("backupRoot").is_none());
+        let mut expected = raw;
+        expected["restoreExtraBackup"] = json!(true);
+        assert_eq!(settings.to_file_value().unwrap(), expected);
+    }
+
+    #[test]
+    fn valid_and_missing_settings() {
+        let settings: Settings = serde_json::from_value(json!({
+            "retention": null,
+            "backupRoot": std::env::temp_dir(),
+            "backupFormat": "dedup",
+        }))
+        .unwrap();
+        assert!(settings.invalid.is_empty());
+        assert!(settings.retention.is_none());
+        assert!(settings.backup_root.is_some());
+        assert_eq!(settings.backup_format, BackupFormat::Snapshot);
+    }
+}
@@ -0,0 +1,171 @@
+use crate::manifest::{source_entries, ArchiveSource, BackupManifest, HashingReader, ManifestFile};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{copy, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// 去重存储的快照文件扩展名：快照本身就是一份 JSON 清单，文件内容按哈希存放在 `store/objects`
+pub const SNAPSHOT_EXTENSION: &str = "snapshot";
+
+/// 内容寻址存储：`<backup_dir>/store/objects/<哈希前两位>/<完整哈希>`
+/// 同一个游戏的多份快照中内容相同的文件只保存一次
+pub struct BlobStore {
+    root: PathBuf,
+}
+
+impl BlobStore {
+    /// 打开（必要时创建）备份目录下的存储
+    pub fn open(backup_dir: &Path) -> Result<Self, String> {
+        let root = backup_dir.join("store");
+        fs::create_dir_all(root.join("objects"))
+            .and_then(|_| fs::create_dir_all(root.join("tmp")))
+            .map_err(|e| format!("创建去重存储目录失败: {e}"))?;
+        Ok(BlobStore { root })
+    }
+
+    /// 哈希来自快照文件，快照可能是外部文件：只接受 64 位小写十六进制，避免借助 `..` 等跳出存储目录
+    fn blob_path(&self, sha256: &str) -> Result<PathBuf, String> {
+        let valid = sha256.len() == 64
+            && sha256.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b));
+        if !valid {
+            return Err(format!("快照中的内容哈希无效: {sha256}"));
+        }
+        Ok(self.root.join("objects").join(&sha256[..2]).join(sha256))
+    }
+
+    pub fn contains(&self, sha256: &str) -> bool {
+        self.blob_path(sha256).is_ok_and(|p| p.is_file())
+    }
+
+    /// 写入一个文件：先边算哈希边复制到临时文件，再按哈希改名；已存在相同内容时直接丢弃临时文件
+    /// 返回哈希与字节数
+    pub fn put_file(&self, path: &Path) -> Result<(String, u64), String> {
+        let src = File::open(path).map_err(|e| format!("读取文件失败: {e}"))?;
+        let tmp_path = self.root.join("tmp").join(format!(
+            "{}-{}",
+            std::process::id(),
+            chrono::Local::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+
+        let mut reader = HashingReader::new(src);
+        let size = {
+            let tmp = File::create(&tmp_path).map_err(|e| format!("写入去重存储失败: {e}"))?;
+            let mut writer = BufWriter::new(tmp);
+            let size = copy(&mut reader, &mut writer).map_err(|e| format!("写入去重存储失败: {e}"))?;
+            writer.flush().map_err(|e| format!("写入去重存储失败: {e}"))?;
+            size
+        };
+        let sha256 = reader.finish();
+
+        let blob = self.blob_path(&sha256)?;
+        if blob.is_file() {
+            let _ = fs::remove_file(&tmp_path);
+        } else {
+            if let Some(parent) = blob.parent() {
+                fs::create_dir_all(parent).map_err(|e| format!("写入去重存储失败: {e}"))?;
+            }
+            fs::rename(&tmp_path, &blob).map_err(|e| {
+                let _ = fs::remove_file(&tmp_path);
+                format!("写入去重存储失败: {e}")
+            })?;
+        }
+        Ok((sha256, size))
+    }
+
+    /// 打开指定哈希的内容
+    pub fn open_blob(&self, sha256: &str) -> Result<File, String> {
+        File::open(self.blob_path(sha256)?).map_err(|e| format!("去重存储中缺少内容 {sha256}: {e}"))
+    }
+
+    /// 从另一个存储复制指定内容（已存在时跳过），用于迁移快照
+    pub fn copy_blob_from(&self, other: &BlobStore, sha256: &str) -> Result<(), String> {
+        let blob = self.blob_path(sha256)?;
+        let source = other.blob_path(sha256)?;
+        if blob.is_file() {
+            return Ok(());
+        }
+        if let Some(parent) = blob.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("写入去重存储失败: {e}"))?;
+        }
+        let tmp_path = self.root.join("tmp").join(sha256);
+        fs::copy(source, &tmp_path)
+            .and_then(|_| fs::rename(&tmp_path, &blob))
+            .map_err(|e| {
+                let _ = fs::remove_file(&tmp_path);
+                format!("复制去重存储内容 {sha256} 失败: {e}")
+            })
+    }
+
+    /// 清理不再被任何快照引用的内容，返回删除的数量
+    /// 直接删除而不是移入回收站：单独的哈希文件对用户没有意义
+    pub fn collect_garbage(&self, referenced: &HashSet<String>) -> Result<usize, String> {
+        let mut removed = 0;
+        for entry in WalkDir::new(self.root.join("objects")).min_depth(2) {
+            let entry = entry.map_err(|e| format!("遍历去重存储失败: {e}"))?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy();
+            if !referenced.contains(name.as_ref()) {
+                fs::remove_file(entry.path()).map_err(|e| format!("清理去重存储失败: {e}"))?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+/// 以去重方式备份目录：文件内容写入存储，快照文件只记录清单（目录单独记在 `dirs`）
+pub fn snapshot_directory(
+    sources: &[ArchiveSource],
+    dest: &Path,
+    store: &BlobStore,
+    manifest: &mut BackupManifest,
+) -> Result<(), String> {
+    for entry in source_entries(sources)? {
+        if entry.is_dir {
+            manifest.dirs.push(entry.name);
+            continue;
+        }
+        let (sha256, size) = store.put_file(&entry.path)?;
+        manifest.files.push(ManifestFile {
+            path: entry.name,
+            size,
+            modified: fs::metadata(&entry.path)
+                .ok()
+                .and_then(|m| m.modified().ok())
+                .and_then(|t| t.duration_since(std::time::SystemTime::UNIX_EPOCH).ok())
+                .map(|d| d.as_millis() as i64),
+            sha256,
+        });
+    }
+
+    fs::write(dest, manifest.to_json()?).map_err(|e| format!("写入快照失败: {e}"))
+}
+
+/// 读取快照文件
+pub fn read_snapshot(path: &Path) -> Result<BackupManifest, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("读取快照失败: {e}"))?;
+    BackupManifest::from_json(&content)
+}
+
+/// 收集备份目录中所有快照引用的内容哈希
+pub fn referenced_blobs(backup_dir: &Path) -> Result<HashSet<String>, String> {
+    let mut referenced = HashSet::new();
+    let entries = fs::read_dir(backup_dir).map_err(|e| format!("读取备份目录失败: {e}"))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_snapshot = path
+            .extension()
+            .and_then(|s| s.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case(SNAPSHOT_EXTENSION));
+        if !is_snapshot {
+            continue;
+        }
+        // 读不出来的快照不能当作“没有引用”，否则会误删仍然需要的内容
+        let snapshot = read_snapshot(&path)?;
+        referenced.extend(snapshot.files.into_iter().map(|f| f.sha256));
+    }
+    Ok(referenced)
+}
@@ -0,0 +1,214 @@
+/// Valve KeyValues 文本格式（libraryfolders.vdf、appmanifest_*.acf、loginusers.vdf 等）的解析
+/// 只处理键值与嵌套块；`//` 注释、`#include` 等指令以及 `[$WIN32]` 这类条件标记会被忽略
+#[derive(Debug, Clone, PartialEq)]
+pub enum VdfValue {
+    Str(String),
+    Map(Vec<(String, VdfValue)>),
+}
+
+impl VdfValue {
+    /// 按键查找子项（VDF 的键不区分大小写）
+    pub fn get(&self, key: &str) -> Option<&VdfValue> {
+        self.entries()
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v)
+    }
+
+    /// 字符串值；块返回 None
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            VdfValue::Str(s) => Some(s),
+            VdfValue::Map(_) => None,
+        }
+    }
+
+    /// 块中的全部键值；字符串返回空列表
+    pub fn entries(&self) -> &[(String, VdfValue)] {
+        match self {
+            VdfValue::Map(entries) => entries,
+            VdfValue::Str(_) => &[],
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    Text(String),
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '{' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            '}' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            '"' => {
+                chars.next();
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => s.push('\n'),
+                            Some('t') => s.push('\t'),
+                            Some(other) => s.push(other),
+                            None => return Err("VDF 字符串缺少结束引号".to_string()),
+                        },
+                        Some(other) => s.push(other),
+                        None => return Err("VDF 字符串缺少结束引号".to_string()),
+                    }
+                }
+                tokens.push(Token::Text(s));
+            }
+            _ => {
+                let mut s = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || c == '{' || c == '}' || c == '"' {
+                        break;
+                    }
+                    s.push(c);
+                    chars.next();
+                }
+                if s.starts_with("//") || s.starts_with('#') {
+                    // 注释与 #include / #base 指令：跳过到行尾
+                    for c in chars.by_ref() {
+                        if c == '\n' {
+                            break;
+                        }
+                    }
+                } else if !(s.starts_with('[') && s.ends_with(']')) {
+                    tokens.push(Token::Text(s));
+                }
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// 解析一个块的内容，直到遇到 `}`（顶层时直到结尾）
+fn parse_entries(
+    tokens: &mut impl Iterator<Item = Token>,
+    nested: bool,
+) -> Result<Vec<(String, VdfValue)>, String> {
+    let mut entries = Vec::new();
+    loop {
+        let key = match tokens.next() {
+            Some(Token::Text(key)) => key,
+            Some(Token::Close) if nested => return Ok(entries),
+            None if !nested => return Ok(entries),
+            None => return Err("VDF 块缺少右括号".to_string()),
+            Some(_) => return Err("VDF 格式错误：缺少键名".to_string()),
+        };
+        let value = match tokens.next() {
+            Some(Token::Text(value)) => VdfValue::Str(value),
+            Some(Token::Open) => VdfValue::Map(parse_entries(tokens, true)?),
+            _ => return Err(format!("VDF 格式错误：{key} 缺少值")),
+        };
+        entries.push((key, value));
+    }
+}
+
+/// 解析 VDF 文本，返回包含所有顶层键的块
+pub fn parse(text: &str) -> Result<VdfValue, String> {
+    let mut tokens = tokenize(text)?.into_iter();
+    Ok(VdfValue::Map(parse_entries(&mut tokens, false)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn str_at<'a>(root: &'a VdfValue, path: &[&str]) -> Option<&'a str> {
+        path.iter()
+            .try_fold(root, |value, key| value.get(key))
+            .and_then(VdfValue::as_str)
+    }
+
+    /// Windows 上的 libraryfolders.vdf：路径中的反斜杠写成 `\\`
+    #[test]
+    fn windows_paths_are_unescaped() {
+        let root = parse(
+            r#""libraryfolders"
+{
+	"0"
+	{
+		"path"		"C:\\Program Files (x86)\\Steam"
+	}
+	"1"
+	{
+		"path"		"D:\\SteamLibrary"
+	}
+}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            str_at(&root, &["libraryfolders", "0", "path"]),
+            Some("C:\\Program Files (x86)\\Steam")
+        );
+        assert_eq!(
+            str_at(&root, &["libraryfolders", "1", "path"]),
+            Some("D:\\SteamLibrary")
+        );
+    }
+
+    #[test]
+    fn escapes_and_case_insensitive_keys() {
+        let root = parse(r#""Root" { "Quote" "say \"hi\"" "Lines" "a\nb\tc" }"#).unwrap();
+        assert_eq!(str_at(&root, &["root", "quote"]), Some("say \"hi\""));
+        assert_eq!(str_at(&root, &["ROOT", "Lines"]), Some("a\nb\tc"));
+    }
+
+    #[test]
+    fn comments_directives_and_conditionals_are_ignored() {
+        let root = parse(
+            r#"#base "base.vdf"
+// 整行注释
+"AppState"
+{
+	"appid"		"10" // 行尾注释
+	"installdir"	"Counter-Strike"//紧跟的注释
+	"LauncherPath"	"C:\\Steam\\steam.exe"	[$WIN32]
+	"Unquoted"	value
+}"#,
+        )
+        .unwrap();
+        let state = root.get("AppState").unwrap();
+        assert_eq!(state.entries().len(), 4);
+        assert_eq!(str_at(state, &["appid"]), Some("10"));
+        assert_eq!(str_at(state, &["installdir"]), Some("Counter-Strike"));
+        assert_eq!(
+            str_at(state, &["LauncherPath"]),
+            Some("C:\\Steam\\steam.exe")
+        );
+        assert_eq!(str_at(state, &["Unquoted"]), Some("value"));
+    }
+
+    #[test]
+    fn malformed_input_is_an_error() {
+        for text in [
+            r#""key" "unterminated"#,
+            r#""key" "ends with escape\"#,
+            r#""root" { "key" "value""#,
+            r#""root" { "key" }"#,
+            r#""key""#,
+            r#"}"#,
+            r#"{ "key" "value" }"#,
+        ] {
+            assert!(parse(text).is_err(), "{text}");
+        }
+        assert_eq!(parse("").unwrap(), VdfValue::Map(Vec::new()));
+    }
+}
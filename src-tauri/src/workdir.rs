@@ -0,0 +1,259 @@
+use crate::paths::get_user_data_dir;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use walkdir::WalkDir;
+
+const WORK_DIR_NAME: &str = "game-sl";
+/// 指定工作目录的环境变量，优先级最高
+const WORKDIR_ENV: &str = "GAME_SL_WORKDIR";
+/// 程序旁边存在此文件时为便携模式：工作目录固定在程序所在目录
+const PORTABLE_MARKER: &str = "game-sl.portable";
+/// 用户数据目录下记录自定义工作目录位置的文件（由 `move_workdir` 写入）
+const LOCATION_FILE: &str = "game-sl.location";
+
+/// 工作目录的来源
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum WorkdirMode {
+    /// 环境变量 GAME_SL_WORKDIR
+    Env,
+    /// 便携模式（程序旁有 game-sl.portable）
+    Portable,
+    /// 用户移动过的自定义位置
+    Custom,
+    /// 默认：程序所在目录下的 game-sl
+    ExeDir,
+    /// 程序目录不可写（例如安装在 Program Files）时退回用户数据目录
+    UserData,
+}
+
+/// 当前工作目录及其来源
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkdirInfo {
+    pub path: String,
+    pub mode: WorkdirMode,
+}
+
+/// 移动工作目录的结果
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkdirMove {
+    pub old_path: String,
+    pub new_path: String,
+    /// 旧目录未能移入回收站时的路径，需要用户手动删除
+    pub stale_path: Option<String>,
+}
+
+/// 解析结果缓存：判断目录是否可写需要实际写文件，不必每次都做
+static WORKDIR: Mutex<Option<(PathBuf, WorkdirMode)>> = Mutex::new(None);
+
+fn exe_dir() -> Result<PathBuf, String> {
+    let exe_path = std::env::current_exe().map_err(|e| format!("无法获取程序路径: {e}"))?;
+    exe_path
+        .parent()
+        .map(Path::to_path_buf)
+        .ok_or_else(|| "无法获取程序所在目录".to_string())
+}
+
+fn location_file() -> Result<PathBuf, String> {
+    Ok(get_user_data_dir()?.join(LOCATION_FILE))
+}
+
+/// 目录可以创建并写入文件
+fn is_writable(dir: &Path) -> bool {
+    if fs::create_dir_all(dir).is_err() {
+        return false;
+    }
+    let probe = dir.join(".write-test");
+    let ok = fs::write(&probe, b"").is_ok();
+    let _ = fs::remove_file(&probe);
+    ok
+}
+
+/// 按优先级确定工作目录：环境变量 > 便携模式 > 自定义位置 > 程序目录（可写时）> 用户数据目录
+fn resolve() -> Result<(PathBuf, WorkdirMode), String> {
+    // 相对路径会随当前工作目录变化，视为未设置
+    if let Some(dir) = std::env::var_os(WORKDIR_ENV).filter(|v| Path::new(v).is_absolute()) {
+        return Ok((PathBuf::from(dir), WorkdirMode::Env));
+    }
+
+    let exe_dir = exe_dir()?;
+    if exe_dir.join(PORTABLE_MARKER).exists() {
+        return Ok((exe_dir.join(WORK_DIR_NAME), WorkdirMode::Portable));
+    }
+
+    let custom = location_file()
+        .ok()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .map(|s| s.trim().to_string())
+        .filter(|s| Path::new(s).is_absolute());
+    if let Some(dir) = custom {
+        return Ok((PathBuf::from(dir), WorkdirMode::Custom));
+    }
+
+    let default = exe_dir.join(WORK_DIR_NAME);
+    if is_writable(&default) {
+        return Ok((default, WorkdirMode::ExeDir));
+    }
+    Ok((get_user_data_dir()?.join(WORK_DIR_NAME), WorkdirMode::UserData))
+}
+
+fn current() -> Result<(PathBuf, WorkdirMode), String> {
+    let mut cached = WORKDIR.lock().map_err(|_| "读取工作目录失败".to_string())?;
+    if let Some(found) = cached.as_ref() {
+        return Ok(found.clone());
+    }
+    let found = resolve()?;
+    *cached = Some(found.clone());
+    Ok(found)
+}
+
+/// 获取当前可读写的工作目录（默认是软件同级目录下的 game-sl）
+pub fn software_workdir() -> Result<PathBuf, String> {
+    let (workdir, _) = current()?;
+    fs::create_dir_all(&workdir).map_err(|e| format!("创建工作目录失败: {e}"))?;
+    Ok(workdir)
+}
+
+/// 当前工作目录及其来源，供设置页展示
+pub fn workdir_info() -> Result<WorkdirInfo, String> {
+    let (path, mode) = current()?;
+    Ok(WorkdirInfo {
+        path: path.to_string_lossy().to_string(),
+        mode,
+    })
+}
+
+/// 复制整个目录，返回复制的文件数与总字节数
+fn copy_dir(src: &Path, dest: &Path) -> Result<(usize, u64), String> {
+    let mut files = 0;
+    let mut bytes = 0;
+    for entry in WalkDir::new(src) {
+        let entry = entry.map_err(|e| format!("遍历工作目录失败: {e}"))?;
+        let relative = entry
+            .path()
+            .strip_prefix(src)
+            .map_err(|e| format!("路径处理失败: {e}"))?;
+        let target = dest.join(relative);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target).map_err(|e| format!("创建目录失败: {e}"))?;
+        } else if entry.file_type().is_file() {
+            bytes += fs::copy(entry.path(), &target)
+                .map_err(|e| format!("复制 {} 失败: {e}", relative.display()))?;
+            files += 1;
+        }
+    }
+    Ok((files, bytes))
+}
+
+/// 复制目录并核对文件数与总大小
+fn copy_verified(src: &Path, dest: &Path) -> Result<(), String> {
+    let (files, bytes) = copy_dir(src, dest)?;
+    let (new_files, new_bytes) = dir_totals(dest)?;
+    if (new_files, new_bytes) != (files, bytes) {
+        return Err(format!(
+            "复制结果不一致：应有 {files} 个文件 {bytes} 字节，实际 {new_files} 个文件 {new_bytes} 字节"
+        ));
+    }
+    Ok(())
+}
+
+/// 移动目录：同一磁盘上直接改名；跨磁盘时先复制并核对，再删除原目录
+/// 目标目录必须不存在或为空
+pub fn move_dir(from: &Path, to: &Path) -> Result<(), String> {
+    let occupied = fs::read_dir(to)
+        .map(|mut entries| entries.next().is_some())
+        .unwrap_or(false);
+    if occupied {
+        return Err(format!("目标目录不为空: {}", to.display()));
+    }
+    let _ = fs::remove_dir(to);
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {e}"))?;
+    }
+
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    if let Err(e) = copy_verified(from, to) {
+        let _ = fs::remove_dir_all(to);
+        return Err(e);
+    }
+    // 新位置已经完整，原目录删不掉只会多占空间，不影响使用
+    let _ = fs::remove_dir_all(from);
+    Ok(())
+}
+
+/// 把整个工作目录（配置、备份、额外备份）移动到新位置，并记住新位置
+/// 先完整复制并核对文件数与大小，全部成功后才切换，旧目录最后送回收站
+/// 环境变量或便携模式决定的工作目录不能移动（下次启动仍会回到原处）
+pub fn move_workdir(target: &Path) -> Result<WorkdirMove, String> {
+    let (old, mode) = current()?;
+    match mode {
+        WorkdirMode::Env => return Err(format!("工作目录由环境变量 {WORKDIR_ENV} 指定，无法移动")),
+        WorkdirMode::Portable => {
+            return Err(format!("当前为便携模式，请先删除程序目录下的 {PORTABLE_MARKER}"))
+        }
+        _ => {}
+    }
+
+    if !target.is_absolute() {
+        return Err("目标路径必须是绝对路径".to_string());
+    }
+    if target.starts_with(&old) || old.starts_with(target) {
+        return Err("目标路径不能与当前工作目录互相包含".to_string());
+    }
+    let occupied = fs::read_dir(target)
+        .map(|mut entries| entries.next().is_some())
+        .unwrap_or(false);
+    if occupied {
+        return Err("目标目录不为空".to_string());
+    }
+    if !is_writable(target) {
+        return Err("目标目录不可写".to_string());
+    }
+
+    if let Err(e) = copy_verified(&old, target) {
+        let _ = fs::remove_dir_all(target);
+        return Err(e);
+    }
+
+    let location = location_file()?;
+    if let Some(parent) = location.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建用户数据目录失败: {e}"))?;
+    }
+    if let Err(e) = fs::write(&location, target.to_string_lossy().as_bytes()) {
+        let _ = fs::remove_dir_all(target);
+        return Err(format!("记录工作目录位置失败: {e}"));
+    }
+
+    if let Ok(mut cached) = WORKDIR.lock() {
+        *cached = Some((target.to_path_buf(), WorkdirMode::Custom));
+    }
+
+    let stale_path = trash::delete(&old)
+        .err()
+        .map(|_| old.to_string_lossy().to_string());
+    Ok(WorkdirMove {
+        old_path: old.to_string_lossy().to_string(),
+        new_path: target.to_string_lossy().to_string(),
+        stale_path,
+    })
+}
+
+/// 统计目录下的文件数与总字节数
+fn dir_totals(dir: &Path) -> Result<(usize, u64), String> {
+    let mut files = 0;
+    let mut bytes = 0;
+    for entry in WalkDir::new(dir) {
+        let entry = entry.map_err(|e| format!("遍历目录失败: {e}"))?;
+        if entry.file_type().is_file() {
+            files += 1;
+            bytes += entry.metadata().map(|m| m.len()).unwrap_or(0);
+        }
+    }
+    Ok((files, bytes))
+}
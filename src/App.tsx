@@ -34,13 +34,14 @@ function App() {
   } = useRestoreFlow()
 
   const onError = useCallback((msg:string) => messageApi.error(msg), [messageApi])
+  const onWarning = useCallback((msg: string) => messageApi.warning(msg), [messageApi])
 
   const {
     loading,
     checkingPaths,
     config,
     setConfig,
-    steamUIDs,
+    steamUsers,
     selectedSteamUID,
     setSelectedSteamUID,
     pathState,
@@ -51,13 +52,15 @@ function App() {
     moveGameUp,
     moveGameDown,
     pinGameTop,
-  } = useAppState({ onError })
+  } = useAppState({ onError, onWarning })
 
   const {
     useRelativeTime,
     restoreExtraBackup,
+    backupFormat,
     updateUseRelativeTime,
     updateRestoreExtraBackup,
+    updateBackupFormat,
   } = useSettings(onError)
   const {
     backupModalOpen,
@@ -78,6 +81,7 @@ function App() {
     closeEditRemark,
     submitEditRemark,
     handleDeleteBackup,
+    handleTogglePin,
     handleRestore,
     openBackupFolder,
   } = useBackups({
@@ -108,7 +112,7 @@ function App() {
   }, [config, selectedSteamUID, pathState])
 
   const renderGameCard = (game: GameEntry) => {
-    const state = pathState[game.name]
+    const state = pathState[game.id]
     const noSteam = game.type === 'steam' && !hasSteam
     const unknown = state === undefined
     const missingSave = state ? !state.exists : false
@@ -122,11 +126,11 @@ function App() {
       : ''
 
     return (
-      <div key={game.name} className="cards-grid-item">
+      <div key={game.id} className="cards-grid-item">
         <GameCard
           game={game}
           pathState={state}
-          resolvedPath={resolveTemplate(game.path)}
+          resolvedPath={state?.resolved ?? resolveTemplate(game.path)}
           disabled={disabled}
           checkingPaths={checkingPaths}
           statusText={statusText}
@@ -153,7 +157,7 @@ function App() {
           activePage={activePage}
           onChangePage={(page) => setActivePage(page)}
           hasSteam={hasSteam}
-          steamUIDs={steamUIDs}
+          steamUsers={steamUsers}
           selectedSteamUID={selectedSteamUID}
           onSelectSteamUID={setSelectedSteamUID}
           onReload={refreshBaseInfo}
@@ -167,8 +171,10 @@ function App() {
               <SettingsPage
                 useRelativeTime={useRelativeTime}
                 restoreExtraBackup={restoreExtraBackup}
+                backupFormat={backupFormat}
                 onToggleRelativeTime={updateUseRelativeTime}
                 onToggleRestoreExtraBackup={updateRestoreExtraBackup}
+                onChangeBackupFormat={updateBackupFormat}
               />
             )}
             {activePage === 'about' && (
@@ -195,6 +201,7 @@ function App() {
         onEditRemark={openEditRemark}
         onRestore={handleRestore}
         onDelete={handleDeleteBackup}
+        onTogglePin={handleTogglePin}
         deletingKey={deletingBackupKey}
         onOpenDir={openBackupFolder}
         editRemarkOpen={editRemarkOpen}
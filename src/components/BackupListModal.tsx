@@ -18,6 +18,7 @@ type Props = {
   onEdit: (item: BackupEntry) => void
   onRestore: (item: BackupEntry) => void
   onDelete: (item: BackupEntry) => void
+  onTogglePin: (item: BackupEntry) => void
   deletingKey?: string | null
   onOpenDir: () => void
   useRelativeTime: boolean
@@ -33,6 +34,7 @@ export default function BackupListModal({
   onEdit,
   onRestore,
   onDelete,
+  onTogglePin,
   deletingKey,
   onOpenDir,
   useRelativeTime,
@@ -73,6 +75,9 @@ export default function BackupListModal({
                   <Button size="small" key="edit" onClick={() => onEdit(item)}>
                     编辑
                   </Button>,
+                  <Button size="small" key="pin" onClick={() => onTogglePin(item)}>
+                    {item.pinned ? '解锁' : '锁定'}
+                  </Button>,
                   <Button
                     size="small"
                     key="delete"
@@ -99,6 +104,16 @@ export default function BackupListModal({
                           ? '其他备份文件'
                           : '未知时间'}
                       </Tag>
+                      {item.pinned && (
+                        <Tag color="purple" variant="filled">
+                          已锁定
+                        </Tag>
+                      )}
+                      {!!item.manifest?.accounts.length && (
+                        <Tag color="cyan" variant="filled">
+                          {item.manifest.accounts.length} 个账号
+                        </Tag>
+                      )}
                     </Space>
                   }
                   description={
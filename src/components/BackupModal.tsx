@@ -1,10 +1,12 @@
 import { useEffect, useState } from 'react'
-import { Modal, Input, Space, Typography } from 'antd'
+import { Modal, Input, Space, Typography, Checkbox } from 'antd'
 import { GameEntry } from '../types'
+import { listSaveAccounts } from '../services/tauri'
 
 /**
  * 备份创建模态框：为选定游戏创建备份，支持填写备注
  * 用途：输入备注并触发备份流程，展示源路径信息
+ * 路径含 {SteamUID} 且有多个账号存在存档时，可选择把所有账号备份进同一个文件
  */
 
 const { Text } = Typography
@@ -14,23 +16,38 @@ type Props = {
   game: GameEntry | null
   resolvedPath: string | null
   onCancel: () => void
-  onSubmit: (remark: string) => Promise<void>
+  onSubmit: (remark: string, allSteamUids: boolean) => Promise<void>
 }
 
 /** 备份创建模态框组件 */
 export default function BackupModal({ open, game, resolvedPath, onCancel, onSubmit }: Props) {
   const [remark, setRemark] = useState('')
   const [loading, setLoading] = useState(false)
+  const [accounts, setAccounts] = useState<string[]>([])
+  const [allSteamUids, setAllSteamUids] = useState(false)
 
   useEffect(() => {
-    // 每次打开或切换游戏时重置备注
-    if (open) setRemark('')
-  }, [open, game?.name])
+    // 每次打开或切换游戏时重置备注与账号选项
+    if (!open) return
+    setRemark('')
+    setAllSteamUids(false)
+    setAccounts([])
+    if (!game) return
+    let cancelled = false
+    listSaveAccounts(game.path, game.steamAppId)
+      .then((list) => {
+        if (!cancelled) setAccounts(list)
+      })
+      .catch(() => {})
+    return () => {
+      cancelled = true
+    }
+  }, [open, game?.name, game?.path])
 
   const handleOk = async () => {
     setLoading(true)
     try {
-      await onSubmit(remark)
+      await onSubmit(remark, allSteamUids)
     } finally {
       setLoading(false)
     }
@@ -55,7 +72,14 @@ export default function BackupModal({ open, game, resolvedPath, onCancel, onSubm
           value={remark}
           onChange={(e) => setRemark(e.target.value)}
         />
-        {game && resolvedPath && <Text type="secondary">备份源路径：{resolvedPath}</Text>}
+        {accounts.length > 1 && (
+          <Checkbox checked={allSteamUids} onChange={(e) => setAllSteamUids(e.target.checked)}>
+            备份所有 Steam 账号的存档（共 {accounts.length} 个：{accounts.join('、')}）
+          </Checkbox>
+        )}
+        {game && resolvedPath && (
+          <Text type="secondary">备份源路径：{allSteamUids ? game.path : resolvedPath}</Text>
+        )}
       </Space>
     </Modal>
   )
@@ -49,7 +49,7 @@ export function GameCard({
   }
 
   return (
-    <Card key={game.name} className="game-card">
+    <Card key={game.id} className="game-card">
       <div className="game-card__inner">
         <div className="game-card__icon">
           <img src={resolveIconSrc(game.icon)} alt={game.name} />
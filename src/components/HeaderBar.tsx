@@ -1,4 +1,5 @@
 import { Button, Divider, Flex, Layout, Segmented, Select, Space, Typography } from 'antd'
+import { SteamUser } from '../types'
 
 const { Title, Text } = Typography
 
@@ -9,7 +10,7 @@ export type HeaderBarProps = {
   activePage: 'main' | 'settings' | 'about'
   onChangePage: (page: 'main' | 'settings' | 'about') => void
   hasSteam: boolean
-  steamUIDs: string[]
+  steamUsers: SteamUser[]
   selectedSteamUID?: string
   onSelectSteamUID: (uid?: string) => void
   onReload: () => void
@@ -22,7 +23,7 @@ export function HeaderBar({
   activePage,
   onChangePage,
   hasSteam,
-  steamUIDs,
+  steamUsers,
   selectedSteamUID,
   onSelectSteamUID,
   onReload,
@@ -56,8 +57,11 @@ export function HeaderBar({
               style={{ minWidth: 200 }}
               value={selectedSteamUID}
               onChange={onSelectSteamUID}
-              disabled={!hasSteam || steamUIDs.length === 0}
-              options={steamUIDs.map((id) => ({ label: id, value: id }))}
+              disabled={!hasSteam || steamUsers.length === 0}
+              options={steamUsers.map((user) => ({
+                label: user.personaName ? `${user.personaName}（${user.accountId}）` : user.accountId,
+                value: user.accountId,
+              }))}
             />
           </div>
           <Button onClick={onReload}>重新加载</Button>
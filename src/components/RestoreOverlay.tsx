@@ -1,7 +1,7 @@
 import { Alert, Button, Modal, Space, Steps, Typography } from 'antd'
 
 /** 复原流程步骤键 */
-export type RestoreStepKey = 'check' | 'extra' | 'delete' | 'extract' | 'update'
+export type RestoreStepKey = 'check' | 'extra' | 'extract' | 'verify' | 'swap' | 'update'
 /** 复原流程步骤状态 */
 export type RestoreStepState = {
   key: RestoreStepKey
@@ -12,7 +12,7 @@ type Props = {
   backupModalOpen: boolean
   backupTarget: GameEntry | null
   onBackupCancel: () => void
-  onBackupSubmit: (remark: string) => Promise<void>
+  onBackupSubmit: (remark: string, allSteamUids: boolean) => Promise<void>
   // backup list
   backupListOpen: boolean
   backupListLoading: boolean
@@ -22,6 +22,7 @@ type Props = {
   onEditRemark: (item: BackupEntry) => void
   onRestore: (item: BackupEntry) => void
   onDelete: (item: BackupEntry) => void
+  onTogglePin: (item: BackupEntry) => void
   deletingKey?: string | null
   onOpenDir: () => Promise<void> | void
   // edit remark
@@ -47,6 +48,7 @@ export default function BackupFeature({
   onEditRemark,
   onRestore,
   onDelete,
+  onTogglePin,
   deletingKey,
   onOpenDir,
   editRemarkOpen,
@@ -76,6 +78,7 @@ export default function BackupFeature({
         onOpenDir={onOpenDir}
         onRestore={onRestore}
         onDelete={onDelete}
+        onTogglePin={onTogglePin}
         deletingKey={deletingKey}
         useRelativeTime={useRelativeTime}
       />
@@ -1,10 +1,11 @@
 import { useState } from 'react'
-import { BackupEntry, GameEntry, AppConfig } from '../../types'
+import { BackupEntry, BackupResponse, GameEntry, AppConfig } from '../../types'
 import {
   backupGame,
   listBackups,
   updateBackupRemark,
   deleteBackup,
+  setBackupPinned,
   restoreBackup,
   getBackupDir,
 } from '../../services/tauri'
@@ -59,13 +60,14 @@ export interface UseBackupsReturn {
   // actions
   openBackupModal: (game: GameEntry) => void
   closeBackupModal: () => void
-  submitBackup: (remark: string) => Promise<void>
+  submitBackup: (remark: string, allSteamUids: boolean) => Promise<void>
   openBackupList: (game: GameEntry) => Promise<void>
   closeBackupList: () => void
   openEditRemark: (item: BackupEntry) => void
   closeEditRemark: () => void
   submitEditRemark: (newRemark: string) => Promise<void>
   handleDeleteBackup: (item: BackupEntry) => void
+  handleTogglePin: (item: BackupEntry) => Promise<void>
   handleRestore: (item: BackupEntry) => void
   openBackupFolder: () => Promise<void>
   // helpers
@@ -101,18 +103,47 @@ export function useBackups({
 
   const closeBackupModal = () => setBackupModalOpen(false)
 
-  const submitBackup = async (remark: string) => {
+  const notifyBackupDone = (result: BackupResponse) => {
+    messageApi.success('备份完成')
+    if (result.pruneErrors.length > 0) {
+      messageApi.error(`清理旧备份失败：${result.pruneErrors.join('；')}`)
+    }
+  }
+
+  const submitBackup = async (remark: string, allSteamUids: boolean) => {
     if (!backupTarget) return
+    const target = backupTarget
     const payloadRemark = remark.trim()
-    const result = await backupGame(
-      backupTarget.name,
-      backupTarget.path,
-      selectedSteamUID ?? null,
-      payloadRemark.length > 0 ? payloadRemark : null
-    )
-    setConfig(result.config)
-    messageApi.success('备份完成')
+    const runBackup = (force: boolean) =>
+      backupGame(
+        target.id,
+        target.path,
+        selectedSteamUID ?? null,
+        payloadRemark.length > 0 ? payloadRemark : null,
+        force,
+        allSteamUids
+      )
+
+    const result = await runBackup(false)
     setBackupModalOpen(false)
+    if (result.outcome === 'unchanged') {
+      // 存档与最近一次备份相同，由用户决定是否仍然备份
+      modal.confirm({
+        title: '存档没有变化',
+        content: `与最近的备份 ${result.fileName} 完全相同，仍要再备份一份吗？`,
+        okText: '仍然备份',
+        cancelText: '取消',
+        centered: true,
+        onOk: async () => {
+          const forced = await runBackup(true)
+          setConfig(forced.config)
+          notifyBackupDone(forced)
+        },
+      })
+      return
+    }
+    setConfig(result.config)
+    notifyBackupDone(result)
   }
 
   const openBackupList = async (game: GameEntry) => {
@@ -121,7 +152,7 @@ export function useBackups({
     setBackupListOpen(true)
     setBackupListLoading(true)
     try {
-      const list = await listBackups(game.name)
+      const list = await listBackups(game.id)
       setBackupList(list)
     } catch (err) {
       console.error(err)
@@ -142,7 +173,7 @@ export function useBackups({
 
   const submitEditRemark = async (newRemark: string) => {
     if (!backupListTarget || !editRemarkTarget) return
-    await updateBackupRemark(backupListTarget.name, editRemarkTarget.fileName, newRemark)
+    await updateBackupRemark(backupListTarget.id, editRemarkTarget.fileName, newRemark)
     messageApi.success('备注已保存')
     setBackupList((prev) =>
       prev.map((b) => (b.fileName === editRemarkTarget.fileName ? { ...b, remark: newRemark } : b))
@@ -154,7 +185,7 @@ export function useBackups({
     if (!backupListTarget) return
     setDeletingBackupKey(item.fileName)
     try {
-      await deleteBackup(backupListTarget.name, item.fileName)
+      await deleteBackup(backupListTarget.id, item.fileName, item.pinned)
       messageApi.success('已删除备份（已送回收站）')
       setBackupList((prev) => prev.filter((b) => b.fileName !== item.fileName))
     } catch (err: any) {
@@ -169,7 +200,9 @@ export function useBackups({
 
     modal.confirm({
       title: `删除备份 ${item.fileName} ？`,
-      content: '将移入回收站并同时删除同名备注文件，确认继续？',
+      content: item.pinned
+        ? '该备份已锁定，仍要移入回收站并同时删除同名备注文件吗？'
+        : '将移入回收站并同时删除同名备注文件，确认继续？',
       okText: '删除',
       okButtonProps: { danger: true },
       cancelText: '取消',
@@ -178,6 +211,18 @@ export function useBackups({
     })
   }
 
+  const handleTogglePin = async (item: BackupEntry) => {
+    if (!backupListTarget) return
+    const pinned = !item.pinned
+    try {
+      await setBackupPinned(backupListTarget.id, item.fileName, pinned)
+      messageApi.success(pinned ? '已锁定备份' : '已解除锁定')
+      setBackupList((prev) => prev.map((b) => (b.fileName === item.fileName ? { ...b, pinned } : b)))
+    } catch (err: any) {
+      messageApi.error(err?.toString?.() ?? '操作失败')
+    }
+  }
+
   const performRestore = async (item: BackupEntry) => {
     if (!backupListTarget) return
 
@@ -185,7 +230,7 @@ export function useBackups({
 
     try {
       const res = await restoreBackup(
-        backupListTarget.name,
+        backupListTarget.id,
         backupListTarget.path,
         item.filePath,
         selectedSteamUID ?? null
@@ -208,7 +253,9 @@ export function useBackups({
 
     modal.confirm({
       title: `确认复原 ${backupListTarget.name} ？`,
-      content: '复原会删除当前存档并解压所选备份，建议确保备份可靠。',
+      content: item.manifest?.accounts.length
+        ? `这是多账号备份，会同时复原 ${item.manifest.accounts.length} 个 Steam 账号的存档：${item.manifest.accounts.join('、')}。`
+        : '复原会删除当前存档并解压所选备份，建议确保备份可靠。',
       okText: '开始复原',
       okButtonProps: { danger: true },
       cancelText: '取消',
@@ -221,7 +268,7 @@ export function useBackups({
 
   const openBackupFolder = async () => {
     try {
-      const dir = await getBackupDir()
+      const dir = await getBackupDir(backupListTarget?.id)
       await openPath(dir)
     } catch (err) {
       console.error(err)
@@ -250,6 +297,7 @@ export function useBackups({
     closeEditRemark,
     submitEditRemark,
     handleDeleteBackup,
+    handleTogglePin,
     handleRestore,
     openBackupFolder,
     // helpers
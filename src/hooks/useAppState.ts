@@ -1,11 +1,14 @@
 import { useCallback, useMemo, useState } from 'react'
-import { AppConfig, GameEntry, PathState } from '../types'
+import { AppConfig, GameEntry, PathState, SteamUser } from '../types'
 import {
   loadConfig,
+  takeConfigRecovery,
+  takeLegacyAdoption,
   getUserFolder,
   getSteamInstallDir,
-  getSteamUIDList,
+  getSteamUsers,
   checkSavePath,
+  resolveTemplatePath,
   reorderGames,
 } from '../services/tauri'
 import { resolveTemplateForDisplay } from '../utils/path'
@@ -20,6 +23,8 @@ import { resolveTemplateForDisplay } from '../utils/path'
 export interface UseAppStateOptions {
   /** 错误提示与上报 */
   onError?: (msg: string, err?: unknown) => void
+  /** 警告提示（例如配置损坏后已自动恢复） */
+  onWarning?: (msg: string) => void
 }
 
 /** 返回值类型：便于 IDE 自动补全 */
@@ -31,7 +36,7 @@ export interface UseAppStateReturn {
   setConfig: (cfg: AppConfig) => void
   userFolder: string
   steamDir: string | null
-  steamUIDs: string[]
+  steamUsers: SteamUser[]
   selectedSteamUID: string | undefined
   setSelectedSteamUID: (uid?: string) => void
   pathState: Record<string, PathState>
@@ -53,14 +58,14 @@ export interface UseAppStateReturn {
 
 // 集中管理与后端交互的基础状态，减少 App 组件样板与耦合
 export function useAppState(options?: UseAppStateOptions): UseAppStateReturn {
-  const { onError } = options || {}
+  const { onError, onWarning } = options || {}
 
   const [loading, setLoading] = useState(true)
   const [checkingPaths, setCheckingPaths] = useState(false)
   const [config, setConfig] = useState<AppConfig | null>(null)
   const [userFolder, setUserFolder] = useState('')
   const [steamDir, setSteamDir] = useState<string | null>(null)
-  const [steamUIDs, setSteamUIDs] = useState<string[]>([])
+  const [steamUsers, setSteamUsers] = useState<SteamUser[]>([])
   const [selectedSteamUID, setSelectedSteamUID] = useState<string | undefined>(undefined)
   const [pathState, setPathState] = useState<Record<string, PathState>>({})
 
@@ -80,20 +85,37 @@ export function useAppState(options?: UseAppStateOptions): UseAppStateReturn {
   const refreshBaseInfo = useCallback(async () => {
     setLoading(true)
     try {
-      const [cfg, userPath, steamPath, uidList] = await Promise.all([
+      const [cfg, userPath, steamPath, users] = await Promise.all([
         loadConfig(),
         getUserFolder(),
         getSteamInstallDir(),
-        getSteamUIDList(),
+        getSteamUsers(),
       ])
 
       setConfig(cfg)
       setUserFolder(userPath)
+
+      // 配置文件损坏时后端已自动恢复，这里只负责提示
+      const recovery = await takeConfigRecovery()
+      if (recovery) {
+        onWarning?.(
+          recovery.restoredFrom
+            ? `配置文件已损坏，已从备份恢复：${recovery.restoredFrom}`
+            : '配置文件已损坏且没有可用备份，已重置为默认配置'
+        )
+      }
+
+      // 从旧版本升级后，未能归位的旧备份留在原处，提示用户手动处理
+      const adoption = await takeLegacyAdoption()
+      if (adoption && adoption.warnings.length > 0) {
+        onWarning?.(`有 ${adoption.warnings.length} 个旧版本的备份未能归位：${adoption.warnings.join('；')}`)
+      }
       setSteamDir(steamPath)
-      setSteamUIDs(uidList)
+      setSteamUsers(users)
 
-      if (!selectedSteamUID && uidList.length > 0) {
-        setSelectedSteamUID(uidList[0])
+      // 后端已按最近登录排序，默认选中第一个
+      if (!selectedSteamUID && users.length > 0) {
+        setSelectedSteamUID(users[0].accountId)
       }
     } catch (err) {
       console.error(err)
@@ -101,7 +123,7 @@ export function useAppState(options?: UseAppStateOptions): UseAppStateReturn {
     } finally {
       setLoading(false)
     }
-  }, [onError, selectedSteamUID])
+  }, [onError, onWarning, selectedSteamUID])
 
   const refreshPathState = useCallback(async () => {
     if (!config) return
@@ -111,12 +133,15 @@ export function useAppState(options?: UseAppStateOptions): UseAppStateReturn {
       const resultPairs = await Promise.all(
         config.games.map(async (game) => {
           let exists = false
+          // 优先使用后端解析的路径（跨平台、支持 Steam 库等），失败时退回前端的简单替换
+          let resolved = resolveTemplate(game.path)
           try {
-            exists = await checkSavePath(game.path, selectedSteamUID ?? null)
+            exists = await checkSavePath(game.path, selectedSteamUID ?? null, game.steamAppId)
+            resolved = await resolveTemplatePath(game.path, selectedSteamUID ?? null, game.steamAppId)
           } catch (err) {
             console.error(err)
           }
-          return [game.name, { exists, resolved: resolveTemplate(game.path) } as PathState]
+          return [game.id, { exists, resolved } as PathState]
         })
       )
       setPathState(Object.fromEntries(resultPairs))
@@ -126,9 +151,9 @@ export function useAppState(options?: UseAppStateOptions): UseAppStateReturn {
   }, [config, resolveTemplate, selectedSteamUID, checkingPaths])
 
   const applyOrder = useCallback(
-    async (names: string[]) => {
+    async (ids: string[]) => {
       try {
-        const cfg = await reorderGames(names)
+        const cfg = await reorderGames(ids)
         setConfig(cfg)
       } catch (err) {
         console.error(err)
@@ -141,11 +166,11 @@ export function useAppState(options?: UseAppStateOptions): UseAppStateReturn {
   const moveGameUp = useCallback(
     async (game: GameEntry) => {
       if (!config) return
-      const names = config.games.map((g) => g.name)
-      const idx = names.indexOf(game.name)
+      const ids = config.games.map((g) => g.id)
+      const idx = ids.indexOf(game.id)
       if (idx <= 0) return
-      ;[names[idx - 1], names[idx]] = [names[idx], names[idx - 1]]
-      await applyOrder(names)
+      ;[ids[idx - 1], ids[idx]] = [ids[idx], ids[idx - 1]]
+      await applyOrder(ids)
     },
     [applyOrder, config]
   )
@@ -153,11 +178,11 @@ export function useAppState(options?: UseAppStateOptions): UseAppStateReturn {
   const moveGameDown = useCallback(
     async (game: GameEntry) => {
       if (!config) return
-      const names = config.games.map((g) => g.name)
-      const idx = names.indexOf(game.name)
-      if (idx < 0 || idx >= names.length - 1) return
-      ;[names[idx], names[idx + 1]] = [names[idx + 1], names[idx]]
-      await applyOrder(names)
+      const ids = config.games.map((g) => g.id)
+      const idx = ids.indexOf(game.id)
+      if (idx < 0 || idx >= ids.length - 1) return
+      ;[ids[idx], ids[idx + 1]] = [ids[idx + 1], ids[idx]]
+      await applyOrder(ids)
     },
     [applyOrder, config]
   )
@@ -165,12 +190,12 @@ export function useAppState(options?: UseAppStateOptions): UseAppStateReturn {
   const pinGameTop = useCallback(
     async (game: GameEntry) => {
       if (!config) return
-      const names = config.games.map((g) => g.name)
-      const idx = names.indexOf(game.name)
+      const ids = config.games.map((g) => g.id)
+      const idx = ids.indexOf(game.id)
       if (idx <= 0) return
-      names.splice(idx, 1)
-      names.unshift(game.name)
-      await applyOrder(names)
+      ids.splice(idx, 1)
+      ids.unshift(game.id)
+      await applyOrder(ids)
     },
     [applyOrder, config]
   )
@@ -183,7 +208,7 @@ export function useAppState(options?: UseAppStateOptions): UseAppStateReturn {
     setConfig,
     userFolder,
     steamDir,
-    steamUIDs,
+    steamUsers,
     selectedSteamUID,
     setSelectedSteamUID,
     pathState,
@@ -35,13 +35,14 @@ export interface UseRestoreFlowReturn {
 
 // 负责复原流程的状态管理与错误解析，避免 App 组件过度膨胀
 export function useRestoreFlow(): UseRestoreFlowReturn {
-  const restoreStepOrder: RestoreStepKey[] = ['check', 'extra', 'delete', 'extract', 'update']
+  const restoreStepOrder: RestoreStepKey[] = ['check', 'extra', 'extract', 'verify', 'swap', 'update']
 
   const buildInitialRestoreSteps = (): RestoreStepState[] => [
     { key: 'check', title: '校验路径与备份文件', status: 'wait' },
     { key: 'extra', title: '复原前额外备份', status: 'wait' },
-    { key: 'delete', title: '删除现有存档', status: 'wait' },
-    { key: 'extract', title: '解压所选备份', status: 'wait' },
+    { key: 'extract', title: '解压到临时目录', status: 'wait' },
+    { key: 'verify', title: '校验解压结果', status: 'wait' },
+    { key: 'swap', title: '替换现有存档', status: 'wait' },
     { key: 'update', title: '更新记录', status: 'wait' },
   ]
 
@@ -61,10 +62,12 @@ export function useRestoreFlow(): UseRestoreFlowReturn {
         return 'check'
       case 'EXTRA_BACKUP':
         return 'extra'
-      case 'DELETE':
-        return 'delete'
       case 'EXTRACT':
         return 'extract'
+      case 'VERIFY':
+        return 'verify'
+      case 'SWAP':
+        return 'swap'
       case 'UPDATE_CONFIG':
         return 'update'
       default:
@@ -1,5 +1,5 @@
 import { useCallback, useEffect, useState } from 'react'
-import { AppConfig } from '../types'
+import { AppConfig, BackupFormat } from '../types'
 import { setSetting, loadConfig } from '../services/tauri'
 
 /** 返回值类型：设置相关状态与动作 */
@@ -10,10 +10,14 @@ export interface UseSettingsReturn {
   useRelativeTime: boolean
   /** 复原前是否额外备份现有存档 */
   restoreExtraBackup: boolean
+  /** 新建备份使用的压缩格式 */
+  backupFormat: BackupFormat
   /** 更新相对时间偏好并保存配置 */
   updateUseRelativeTime: (checked: boolean) => Promise<void>
   /** 更新复原前额外备份偏好并保存配置 */
   updateRestoreExtraBackup: (checked: boolean) => Promise<void>
+  /** 更新备份格式并保存配置 */
+  updateBackupFormat: (format: BackupFormat) => Promise<void>
 }
 
 /**
@@ -23,6 +27,7 @@ export interface UseSettingsReturn {
 export function useSettings(onError?: (msg: string, err?: unknown) => void): UseSettingsReturn {
   const [useRelativeTime, setUseRelativeTime] = useState(true)
   const [restoreExtraBackup, setRestoreExtraBackup] = useState(true)
+  const [backupFormat, setBackupFormat] = useState<BackupFormat>('zip')
   const [config, setConfig] = useState<AppConfig | null>(null)
   const [loading, setLoading] = useState(true)
 
@@ -33,10 +38,10 @@ export function useSettings(onError?: (msg: string, err?: unknown) => void): Use
         const cfg = await loadConfig()
         if (!mounted) return
         setConfig(cfg)
-        const pref = (cfg.settings as any)?.useRelativeTime
-        setUseRelativeTime(typeof pref === 'boolean' ? pref : true)
-        const restorePref = (cfg.settings as any)?.restoreExtraBackup
-        setRestoreExtraBackup(typeof restorePref === 'boolean' ? restorePref : true)
+        // 设置项的类型与默认值由后端保证
+        setUseRelativeTime(cfg.settings.useRelativeTime)
+        setRestoreExtraBackup(cfg.settings.restoreExtraBackup)
+        setBackupFormat(cfg.settings.backupFormat)
       } catch (err) {
         onError?.('加载配置失败', err)
       } finally {
@@ -74,12 +79,27 @@ export function useSettings(onError?: (msg: string, err?: unknown) => void): Use
     [onError]
   )
 
+  const updateBackupFormat = useCallback(
+    async (format: BackupFormat) => {
+      try {
+        const cfg = await setSetting('backupFormat', format)
+        setConfig(cfg)
+        setBackupFormat(format)
+      } catch (err) {
+        onError?.('保存备份格式失败', err)
+      }
+    },
+    [onError]
+  )
+
   return {
     loading,
     config,
     useRelativeTime,
     restoreExtraBackup,
+    backupFormat,
     updateUseRelativeTime,
     updateRestoreExtraBackup,
+    updateBackupFormat,
   }
 }
@@ -1,11 +1,14 @@
-import { Flex, Space, Switch, Typography } from 'antd'
+import { Flex, Radio, Space, Switch, Typography } from 'antd'
+import { BackupFormat } from '../types'
 
 /** 设置页面：提供用户偏好开关 */
 type Props = {
   useRelativeTime: boolean
   restoreExtraBackup: boolean
+  backupFormat: BackupFormat
   onToggleRelativeTime: (checked: boolean) => void
   onToggleRestoreExtraBackup: (checked: boolean) => void
+  onChangeBackupFormat: (format: BackupFormat) => void
 }
 
 const { Text } = Typography
@@ -14,8 +17,10 @@ const { Text } = Typography
 export default function SettingsPage({
   useRelativeTime,
   restoreExtraBackup,
+  backupFormat,
   onToggleRelativeTime,
   onToggleRestoreExtraBackup,
+  onChangeBackupFormat,
 }: Props) {
   return (
     <Flex vertical gap={16} style={{ padding: 16 }}>
@@ -34,6 +39,23 @@ export default function SettingsPage({
           <Switch checked={restoreExtraBackup} onChange={onToggleRestoreExtraBackup} />
         </Space>
       </Flex>
+
+      <Flex align="center" gap={12}>
+        <Text strong>备份格式</Text>
+        <Space>
+          <Text type="secondary">7z 体积更小但速度较慢；去重存储只保存有变化的文件</Text>
+          <Radio.Group
+            value={backupFormat}
+            onChange={(e) => onChangeBackupFormat(e.target.value)}
+            optionType="button"
+            options={[
+              { label: 'ZIP', value: 'zip' },
+              { label: '7Z', value: '7z' },
+              { label: '去重存储', value: 'dedup' },
+            ]}
+          />
+        </Space>
+      </Flex>
     </Flex>
   )
 }
@@ -1,10 +1,18 @@
 import { invoke } from '@tauri-apps/api/core'
-import { AppConfig, BackupEntry, BackupResponse, RestoreResponse } from '../types'
+import { AppConfig, BackupEntry, BackupResponse, ConfigRecovery, GameDraft, ImportReport, LegacyAdoption, PruneCandidate, RestoreResponse, RetentionPolicy, SteamUser, VerifyReport, WorkdirInfo, WorkdirMove } from '../types'
 
 export async function loadConfig(): Promise<AppConfig> {
   return invoke<AppConfig>('load_config')
 }
 
+export async function takeConfigRecovery(): Promise<ConfigRecovery | null> {
+  return invoke<ConfigRecovery | null>('take_config_recovery')
+}
+
+export async function takeLegacyAdoption(): Promise<LegacyAdoption | null> {
+  return invoke<LegacyAdoption | null>('take_legacy_adoption')
+}
+
 export async function getUserFolder(): Promise<string> {
   return invoke<string>('get_user_folder')
 }
@@ -17,53 +25,110 @@ export async function getSteamUIDList(): Promise<string[]> {
   return invoke<string[]>('get_steam_uid_list')
 }
 
-export async function checkSavePath(path: string, steamUid?: string | null): Promise<boolean> {
-  return invoke<boolean>('check_save_path', { path, steamUid: steamUid ?? null })
+/** 获取本机的 Steam 账号，最近登录的排在最前 */
+export async function getSteamUsers(): Promise<SteamUser[]> {
+  return invoke<SteamUser[]>('get_steam_users')
+}
+
+export async function checkSavePath(path: string, steamUid?: string | null, appId?: number | null): Promise<boolean> {
+  return invoke<boolean>('check_save_path', { path, steamUid: steamUid ?? null, appId: appId ?? null })
 }
 
-export async function resolveTemplatePath(template: string, steamUid?: string | null): Promise<string> {
+export async function resolveTemplatePath(
+  template: string,
+  steamUid?: string | null,
+  appId?: number | null
+): Promise<string> {
   // 返回解析后的绝对路径字符串（后端 PathBuf 会序列化为字符串）
-  const resolved = await invoke<string>('resolve_template_path', { template, steamUid: steamUid ?? null })
+  const resolved = await invoke<string>('resolve_template_path', {
+    template,
+    steamUid: steamUid ?? null,
+    appId: appId ?? null,
+  })
   return resolved
 }
 
+/** 列出存档路径存在的 Steam 账号（路径不含 {SteamUID} 时为空） */
+export async function listSaveAccounts(path: string, appId?: number | null): Promise<string[]> {
+  return invoke<string[]>('list_save_accounts', { path, appId: appId ?? null })
+}
+
 export async function backupGame(
-  gameName: string,
+  gameId: string,
   pathTemplate: string,
   steamUid?: string | null,
-  remark?: string | null
+  remark?: string | null,
+  force?: boolean,
+  allSteamUids?: boolean
 ): Promise<BackupResponse> {
   return invoke<BackupResponse>('backup_game', {
-    gameName,
+    gameId,
     pathTemplate,
     steamUid: steamUid ?? null,
     remark: remark ?? null,
+    force: force ?? false,
+    allSteamUids: allSteamUids ?? false,
   })
 }
 
-export async function listBackups(gameName: string): Promise<BackupEntry[]> {
-  return invoke<BackupEntry[]>('list_backups', { gameName })
+export async function listBackups(gameId: string): Promise<BackupEntry[]> {
+  return invoke<BackupEntry[]>('list_backups', { gameId })
 }
 
-export async function updateBackupRemark(gameName: string, fileName: string, remark: string): Promise<void> {
-  await invoke('update_backup_remark', { gameName, fileName, remark })
+export async function verifyBackup(gameId: string, fileName: string): Promise<VerifyReport> {
+  return invoke<VerifyReport>('verify_backup', { gameId, fileName })
 }
 
-export async function deleteBackup(gameName: string, fileName: string): Promise<void> {
-  await invoke('delete_backup', { gameName, fileName })
+export async function pruneBackups(gameId: string, dryRun?: boolean): Promise<PruneCandidate[]> {
+  return invoke<PruneCandidate[]>('prune_backups', { gameId, dryRun: dryRun ?? false })
+}
+
+/** 清理去重存储中不再被任何快照引用的内容，返回删除的数量 */
+export async function collectBackupGarbage(gameId: string): Promise<number> {
+  return invoke<number>('collect_backup_garbage', { gameId })
+}
+
+/** 设置单个游戏的保留策略；传 null 恢复使用全局策略 */
+export async function setGameRetention(gameId: string, retention: RetentionPolicy | null): Promise<AppConfig> {
+  return invoke<AppConfig>('set_game_retention', { gameId, retention })
+}
+
+export async function updateBackupRemark(gameId: string, fileName: string, remark: string): Promise<void> {
+  await invoke('update_backup_remark', { gameId, fileName, remark })
+}
+
+export async function setBackupPinned(gameId: string, fileName: string, pinned: boolean): Promise<void> {
+  await invoke('set_backup_pinned', { gameId, fileName, pinned })
+}
+
+export async function deleteBackup(gameId: string, fileName: string, force?: boolean): Promise<void> {
+  await invoke('delete_backup', { gameId, fileName, force: force ?? false })
 }
 
 export async function restoreBackup(
-  gameName: string,
+  gameId: string,
   pathTemplate: string,
   backupPath: string,
   steamUid?: string | null
 ): Promise<RestoreResponse> {
-  return invoke<RestoreResponse>('restore_backup', { gameName, pathTemplate, backupPath, steamUid: steamUid ?? null })
+  return invoke<RestoreResponse>('restore_backup', { gameId, pathTemplate, backupPath, steamUid: steamUid ?? null })
 }
 
-export async function getBackupDir(): Promise<string> {
-  return invoke<string>('get_backup_dir')
+export async function getBackupDir(gameId?: string | null): Promise<string> {
+  return invoke<string>('get_backup_dir', { gameId: gameId ?? null })
+}
+
+/** 修改备份位置并移动已有备份；gameId 为空时修改全局备份根目录，location 为空时恢复默认位置 */
+export async function setBackupLocation(gameId: string | null, location: string | null): Promise<AppConfig> {
+  return invoke<AppConfig>('set_backup_location', { gameId, location })
+}
+
+export async function getWorkdirInfo(): Promise<WorkdirInfo> {
+  return invoke<WorkdirInfo>('get_workdir_info')
+}
+
+export async function moveWorkdir(target: string): Promise<WorkdirMove> {
+  return invoke<WorkdirMove>('move_workdir', { target })
 }
 
 export async function setSetting(key: string, value: unknown): Promise<AppConfig> {
@@ -73,3 +138,39 @@ export async function setSetting(key: string, value: unknown): Promise<AppConfig
 export async function reorderGames(order: string[]): Promise<AppConfig> {
   return invoke<AppConfig>('reorder_games', { order })
 }
+
+export async function addGame(game: GameDraft): Promise<AppConfig> {
+  return invoke<AppConfig>('add_game', { game })
+}
+
+export async function updateGame(gameId: string, game: GameDraft): Promise<AppConfig> {
+  return invoke<AppConfig>('update_game', { gameId, game })
+}
+
+export async function removeGame(gameId: string, deleteBackups?: boolean): Promise<AppConfig> {
+  return invoke<AppConfig>('remove_game', { gameId, deleteBackups: deleteBackups ?? false })
+}
+
+export async function renameGame(gameId: string, newName: string): Promise<AppConfig> {
+  return invoke<AppConfig>('rename_game', { gameId, newName })
+}
+
+/** 导出游戏定义到 JSON 文件，gameIds 为空时导出全部，返回导出的数量 */
+export async function exportGames(gameIds: string[], file: string): Promise<number> {
+  return invoke<number>('export_games', { gameIds, file })
+}
+
+/** 从 JSON 文件导入游戏定义；overwrite 为 true 时覆盖同 id 游戏的路径、图标与类型 */
+export async function importGames(file: string, overwrite = false): Promise<ImportReport> {
+  return invoke<ImportReport>('import_games', { file, overwrite })
+}
+
+/** 在本地的 Ludusavi 清单（manifest.yaml）中按名称搜索游戏 */
+export async function searchLudusavi(file: string, query: string): Promise<GameDraft[]> {
+  return invoke<GameDraft[]>('search_ludusavi', { file, query })
+}
+
+/** 把 Ludusavi 清单中选中的游戏加入配置 */
+export async function importLudusavi(file: string, names: string[]): Promise<ImportReport> {
+  return invoke<ImportReport>('import_ludusavi', { file, names })
+}
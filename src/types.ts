@@ -1,17 +1,83 @@
 export type GameEntry = {
+  /** 固定的游戏 id，后端按 id 查找游戏与归属备份 */
+  id: string
   name: string
   path: string
   icon: string
   lastSave?: number
   type?: 'steam' | 'userdata'
+  /** Steam App ID，在 Linux 上用于定位 Proton 前缀 */
+  steamAppId?: number
+  retention?: RetentionPolicy
+  /** 单独的备份目录，为空时使用备份根目录 */
+  backupDir?: string
+}
+
+/** 新增/编辑游戏时提交的字段 */
+export type GameDraft = Pick<GameEntry, 'name' | 'path' | 'icon' | 'type' | 'steamAppId'>
+
+export type RetentionPolicy = {
+  keepLast?: number
+  keepDaily?: number
+  keepWeekly?: number
+  keepMonthly?: number
+  maxTotalSize?: number
+}
+
+export type PruneCandidate = {
+  fileName: string
+  timestamp?: number
+  size: number
+  reason: 'policy' | 'size-limit'
+}
+
+export type BackupFormat = 'zip' | '7z' | 'dedup'
+
+/** 设置项：已知键由后端保证类型，其余键原样保留 */
+export type Settings = {
+  useRelativeTime: boolean
+  restoreExtraBackup: boolean
+  backupFormat: BackupFormat
+  manifestSidecar: boolean
+  retention?: RetentionPolicy
+  /** 备份根目录，为空时使用工作目录下的 backup */
+  backupRoot?: string
+  [key: string]: unknown
 }
 
 export type AppConfig = {
-  settings: Record<string, unknown>
+  settings: Settings
   games: GameEntry[]
   version: number
 }
 
+/** 配置损坏后的自动恢复记录 */
+export type ConfigRecovery = {
+  error: string
+  corruptPath?: string
+  restoredFrom?: string
+}
+
+/** 从配置 v1 升级后旧备份归位的结果 */
+export type LegacyAdoption = {
+  adopted: number
+  warnings: string[]
+}
+
+/** 工作目录来源：环境变量 / 便携模式 / 自定义位置 / 程序目录 / 用户数据目录 */
+export type WorkdirMode = 'env' | 'portable' | 'custom' | 'exe-dir' | 'user-data'
+
+export type WorkdirInfo = {
+  path: string
+  mode: WorkdirMode
+}
+
+export type WorkdirMove = {
+  oldPath: string
+  newPath: string
+  stalePath?: string
+}
+
 export type PathState = {
   exists: boolean
   resolved: string
@@ -24,13 +90,46 @@ export type BackupEntry = {
   remark?: string
   size: number
   timeSource: string
+  manifest?: ManifestSummary
+  verifyStatus: VerifyStatus
+  pinned: boolean
+}
+
+export type VerifyStatus = 'unverified' | 'verified' | 'corrupt' | 'unreadable'
+
+export type VerifyReport = {
+  fileName: string
+  status: VerifyStatus
+  checkedEntries: number
+  hasManifest: boolean
+  problems: string[]
 }
 
+export type ManifestSummary = {
+  gameName: string
+  sourcePath: string
+  steamUid?: string
+  appVersion: string
+  configVersion: number
+  createdAt: number
+  fileCount: number
+  totalSize: number
+  /** 多账号备份包含的 Steam 账号 id */
+  accounts: string[]
+}
+
+export type BackupOutcome = 'created' | 'unchanged'
+
 export type BackupResponse = {
+  outcome: BackupOutcome
   fileName: string
   filePath: string
   timestamp: number
   remarkPath?: string
+  manifestPath?: string
+  pruned: string[]
+  /** 清理旧备份时遇到的错误（不影响本次备份） */
+  pruneErrors: string[]
   config: AppConfig
 }
 
@@ -40,4 +139,43 @@ export type RestoreResponse = {
   backupFile: string
   extraBackupPath?: string
   timestamp: number
+  rejectedEntries: RejectedEntry[]
+  staleSavePath?: string
+  /** 多账号备份复原的 Steam 账号；单账号备份为空 */
+  steamUids: string[]
+}
+
+export type RejectedEntry = {
+  name: string
+  reason: string
+  escapes: boolean
+}
+
+/** 导入游戏清单时未能导入的游戏 */
+export type ImportConflict = {
+  name: string
+  /** 发生冲突的本地游戏名称，字段校验失败时为空 */
+  existing?: string | null
+  reason: string
+}
+
+/** 导入游戏清单的结果（列表中为游戏名称） */
+export type ImportReport = {
+  added: string[]
+  updated: string[]
+  unchanged: string[]
+  conflicts: ImportConflict[]
+  config: AppConfig
+}
+
+/** 本机的 Steam 账号 */
+export type SteamUser = {
+  /** 32 位账号 id，即 {SteamUID} 的取值 */
+  accountId: string
+  steamId64: string
+  personaName?: string | null
+  accountName?: string | null
+  /** 是否为最近一次登录的账号 */
+  mostRecent: boolean
+  timestamp?: number | null
 }